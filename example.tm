@@ -0,0 +1,15 @@
+# adds two numbers represented by 1s and separated with a 0 (same machine as example.csv)
+tracks: 1
+tape: two-way
+initial: q0
+final: q4
+input_alphabet: {0, 1}
+tape_alphabet: {0, 1, □}
+
+δ(q0,1)=(q0,1,R)
+δ(q0,0)=(q1,1,R)
+δ(q1,1)=(q1,1,R)
+δ(q1,□)=(q2,□,L)
+δ(q2,1)=(q3,0,L)
+δ(q3,1)=(q3,1,L)
+δ(q3,□)=(q4,□,R)
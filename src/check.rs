@@ -0,0 +1,260 @@
+use crate::definition::format_function;
+use crate::normalize::all_states;
+use crate::rules::{self, TrackPattern, TrackWrite};
+use crate::Machine;
+use colored::*;
+use std::collections::{HashSet, VecDeque};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    /// The machine can't behave as written.
+    Error,
+    /// Probably a mistake, but the machine still runs.
+    Warning,
+    /// Worth knowing, often harmless.
+    Info,
+}
+
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+pub fn print(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        let label = match diagnostic.severity {
+            Severity::Error => "error".red(),
+            Severity::Warning => "warning".yellow(),
+            Severity::Info => "info".blue(),
+        };
+        println!("{}: {}", label, diagnostic.message);
+    }
+}
+
+/// Statically checks a machine definition without running it.
+pub fn check(machine: &Machine) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |severity, message: String| diagnostics.push(Diagnostic { severity, message });
+    let initial = &machine.states.initial_state;
+    let finals = &machine.states.final_states;
+
+    let mut parsed = Vec::new();
+    for function in &machine.transitions {
+        let patterns =
+            match rules::parse_pattern(&function.lhs.input, machine.tracks, &machine.sets) {
+                Ok(patterns) => patterns,
+                Err(e) => {
+                    report(
+                        Severity::Error,
+                        format!("{}: {}", format_function(function), e),
+                    );
+                    continue;
+                }
+            };
+        let bindings = rules::bindings(&patterns);
+        match rules::parse_replacement(&function.rhs.replacement, machine.tracks, &bindings) {
+            Ok(writes) => parsed.push((function, patterns, writes)),
+            Err(e) => report(
+                Severity::Error,
+                format!("{}: {}", format_function(function), e),
+            ),
+        }
+    }
+
+    // nondeterminism: rules of one state that can match the same cell
+    for (i, (a, a_patterns, _)) in parsed.iter().enumerate() {
+        for (b, b_patterns, _) in &parsed[i + 1..] {
+            if a.lhs.state != b.lhs.state || !overlap(a_patterns, b_patterns) {
+                continue;
+            }
+            let same_rhs = a.rhs == b.rhs;
+            if a.lhs.input == b.lhs.input {
+                if same_rhs {
+                    report(
+                        Severity::Warning,
+                        format!("duplicate rule {}", format_function(a)),
+                    );
+                } else {
+                    report(
+                        Severity::Error,
+                        format!(
+                            "conflicting rules {} and {} (nondeterministic, the first one wins)",
+                            format_function(a),
+                            format_function(b)
+                        ),
+                    );
+                }
+            } else if !same_rhs && rules::specificity(a_patterns) == rules::specificity(b_patterns)
+            {
+                report(
+                    Severity::Warning,
+                    format!(
+                        "rules {} and {} overlap and are equally specific, the first one wins",
+                        format_function(a),
+                        format_function(b)
+                    ),
+                );
+            }
+        }
+    }
+
+    let states = all_states(machine);
+    let mentioned = |state: &String| {
+        state == initial
+            || machine
+                .transitions
+                .iter()
+                .any(|t| &t.lhs.state == state || &t.rhs.state == state)
+    };
+
+    let mut reachable: HashSet<&String> = HashSet::from([initial]);
+    let mut queue: VecDeque<&String> = VecDeque::from([initial]);
+    while let Some(state) = queue.pop_front() {
+        for function in machine.transitions.iter().filter(|t| &t.lhs.state == state) {
+            if reachable.insert(&function.rhs.state) {
+                queue.push_back(&function.rhs.state);
+            }
+        }
+    }
+    // a final state no rule mentions is reported below, once
+    for state in states.iter().filter(|state| mentioned(state)) {
+        if !reachable.contains(state) {
+            report(
+                Severity::Warning,
+                format!("state '{}' is unreachable from '{}'", state, initial),
+            );
+        }
+        if state != initial && !machine.transitions.iter().any(|t| &t.rhs.state == state) {
+            report(
+                Severity::Info,
+                format!("state '{}' is never the target of a rule", state),
+            );
+        }
+    }
+
+    for state in finals {
+        if machine.transitions.iter().any(|t| &t.lhs.state == state) {
+            report(
+                Severity::Warning,
+                format!("final state '{}' has outgoing rules", state),
+            );
+        }
+        if !mentioned(state) {
+            report(
+                Severity::Warning,
+                format!("final state '{}' doesn't appear in any rule", state),
+            );
+        }
+    }
+    if finals.is_empty() {
+        report(
+            Severity::Warning,
+            String::from("there are no final states, every input is rejected"),
+        );
+    }
+
+    if !machine.transitions.iter().any(|t| &t.lhs.state == initial) {
+        if finals.contains(initial) {
+            report(
+                Severity::Warning,
+                format!(
+                    "initial state '{}' has no rules, every input is accepted",
+                    initial
+                ),
+            );
+        } else {
+            report(
+                Severity::Error,
+                format!(
+                    "initial state '{}' has no rules, every input is rejected",
+                    initial
+                ),
+            );
+        }
+    }
+
+    if let Some(gamma) = &machine.tape_alphabet {
+        if let Some(sigma) = &machine.input_alphabet {
+            for symbol in sigma.iter().filter(|c| !gamma.contains(c)) {
+                report(
+                    Severity::Error,
+                    format!("input symbol '{}' is not in the tape alphabet Γ", symbol),
+                );
+            }
+        }
+        // each symbol once, with every rule that uses it
+        let mut outside: Vec<(char, Vec<String>)> = Vec::new();
+        for (function, patterns, writes) in &parsed {
+            let mut used: Vec<char> = Vec::new();
+            for pattern in patterns {
+                match pattern {
+                    TrackPattern::Symbol(c) => used.push(*c),
+                    TrackPattern::Class { symbols, .. } => used.extend(symbols),
+                    TrackPattern::Any => {}
+                }
+            }
+            for write in writes {
+                if let TrackWrite::Symbol(c) = write {
+                    used.push(*c);
+                }
+            }
+            for c in used.into_iter().filter(|c| !gamma.contains(c)) {
+                let rule = format_function(function);
+                match outside.iter_mut().find(|(symbol, _)| *symbol == c) {
+                    Some((_, rules)) if rules.contains(&rule) => {}
+                    Some((_, rules)) => rules.push(rule),
+                    None => outside.push((c, vec![rule])),
+                }
+            }
+        }
+        for (symbol, rules) in outside {
+            report(
+                Severity::Error,
+                format!(
+                    "{} {} '{}', which is not in the tape alphabet Γ",
+                    rules.join(" and "),
+                    if rules.len() == 1 { "uses" } else { "use" },
+                    symbol
+                ),
+            );
+        }
+    }
+
+    for track in 0..machine.tracks {
+        let mut written: Vec<char> = Vec::new();
+        for (_, _, writes) in &parsed {
+            if let TrackWrite::Symbol(c) = writes[track] {
+                if !written.contains(&c) {
+                    written.push(c);
+                }
+            }
+        }
+        for symbol in written {
+            let read = parsed
+                .iter()
+                .any(|(_, patterns, _)| patterns[track].matches(symbol));
+            if !read {
+                let location = if machine.tracks > 1 {
+                    format!(" on track {}", track + 1)
+                } else {
+                    String::new()
+                };
+                report(
+                    Severity::Info,
+                    format!("symbol '{}' is written{} but never read", symbol, location),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Whether some cell matches both patterns.
+fn overlap(a: &[TrackPattern], b: &[TrackPattern]) -> bool {
+    a.iter().zip(b).all(|(x, y)| match (x, y) {
+        (TrackPattern::Any, _) | (_, TrackPattern::Any) => true,
+        (TrackPattern::Symbol(c), other) | (other, TrackPattern::Symbol(c)) => other.matches(*c),
+        (TrackPattern::Class { symbols, .. }, other) => symbols.iter().any(|c| other.matches(*c)),
+    })
+}
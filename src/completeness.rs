@@ -0,0 +1,63 @@
+use crate::normalize::all_states;
+use crate::rules::{self, RuleIndex};
+use crate::{Machine, TransitionFunction, LHS, RHS};
+
+/// States that can't halt on their own: every state but the final ones.
+fn running_states(machine: &Machine) -> Vec<String> {
+    let mut states = all_states(machine);
+    states.retain(|state| !machine.states.final_states.contains(state));
+    states
+}
+
+/// Every (state, cell) pair of a non-final state that no rule covers, over the
+/// declared tape alphabet or else the symbols the machine's rules use.
+pub fn missing(machine: &Machine) -> Vec<(String, String)> {
+    let index = RuleIndex::new(machine);
+    let cells = rules::cells(&rules::tape_alphabet(machine));
+    let mut missing = Vec::new();
+    for state in running_states(machine) {
+        for cell in &cells {
+            if index.fire(&state, cell).is_none() {
+                missing.push((state.clone(), cell.clone()));
+            }
+        }
+    }
+    missing
+}
+
+/// Adds a rule sending every missing pair to `reject`, a new state that is
+/// left non-final so that the machine halts and rejects there. Fails if the
+/// machine already has a state of that name.
+pub fn fill(machine: &mut Machine, reject: &str) -> Result<usize, String> {
+    let taken = machine.states.initial_state == reject
+        || machine
+            .states
+            .final_states
+            .iter()
+            .any(|state| state == reject)
+        || machine
+            .transitions
+            .iter()
+            .any(|f| f.lhs.state == reject || f.rhs.state == reject);
+    if taken {
+        return Err(format!(
+            "'{}' is already a state of the machine, pick a new name for the reject state",
+            reject
+        ));
+    }
+    let missing = missing(machine);
+    for (state, cell) in &missing {
+        machine.transitions.push(TransitionFunction {
+            lhs: LHS {
+                state: state.clone(),
+                input: cell.clone(),
+            },
+            rhs: RHS {
+                state: reject.to_string(),
+                replacement: cell.clone(),
+                direction: 'R',
+            },
+        });
+    }
+    Ok(missing.len())
+}
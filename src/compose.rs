@@ -0,0 +1,259 @@
+use crate::definition;
+use crate::rules::SymbolSet;
+use crate::{KeyStates, Machine, TransitionFunction, LHS, RHS};
+use std::collections::HashMap;
+use std::path::Path;
+
+// A composition wires small machines ("parts") into one flat machine. States
+// of a part are renamed to '<part>.<state>' so parts never clash, and a link
+// identifies a halting state of one part with the initial state of the next,
+// so the flat machine needs no extra steps to move between parts.
+//
+// Composition files list the parts, the part to start with and the links:
+//
+//   part scan = scan.tm
+//   part back = rewind.tm
+//   start scan
+//   scan.found -> back      # branch on the state scan halted in
+//   scan.missing -> reject
+//   back -> accept          # any final state of back
+
+pub const ACCEPT: &str = "accept";
+pub const REJECT: &str = "reject";
+
+/// Where a part continues once it halts.
+pub enum Target {
+    Part(String),
+    Accept,
+    Reject,
+}
+
+struct Link {
+    from: String,
+    /// Halting state the link applies to; `None` means every final state.
+    on: Option<String>,
+    to: Target,
+}
+
+#[derive(Default)]
+pub struct Composition {
+    parts: Vec<(String, Machine)>,
+    start: Option<String>,
+    links: Vec<Link>,
+}
+
+impl Composition {
+    pub fn add(&mut self, name: &str, machine: Machine) -> Result<(), String> {
+        if name.is_empty() || name.contains('.') || name == ACCEPT || name == REJECT {
+            return Err(format!("invalid part name '{}'", name));
+        }
+        if self.part(name).is_some() {
+            return Err(format!("part '{}' is defined twice", name));
+        }
+        self.parts.push((name.to_string(), machine));
+        Ok(())
+    }
+
+    pub fn start(&mut self, name: &str) {
+        self.start = Some(name.to_string());
+    }
+
+    pub fn link(&mut self, from: &str, on: Option<&str>, to: Target) {
+        self.links.push(Link {
+            from: from.to_string(),
+            on: on.map(|s| s.to_string()),
+            to,
+        });
+    }
+
+    fn part(&self, name: &str) -> Option<&Machine> {
+        self.parts.iter().find(|(n, _)| n == name).map(|(_, m)| m)
+    }
+
+    /// Flattens the composition into a single machine.
+    pub fn build(&self) -> Result<Machine, String> {
+        let Some((_, first)) = self.parts.first() else {
+            return Err(String::from("composition has no parts"));
+        };
+        let start = self.start.clone().unwrap_or(self.parts[0].0.clone());
+        let Some(start_machine) = self.part(&start) else {
+            return Err(format!("unknown start part '{}'", start));
+        };
+
+        let mut sets: Vec<SymbolSet> = Vec::new();
+        for (name, machine) in &self.parts {
+            if machine.tracks != first.tracks {
+                return Err(format!(
+                    "part '{}' has {} tracks, expected {}",
+                    name, machine.tracks, first.tracks
+                ));
+            }
+            if machine.tape_mode != first.tape_mode {
+                return Err(format!(
+                    "part '{}' runs on a {} tape, expected {}",
+                    name,
+                    machine.tape_mode.name(),
+                    first.tape_mode.name()
+                ));
+            }
+            for set in &machine.sets {
+                match sets.iter().find(|s| s.name == set.name) {
+                    Some(existing) if existing != set => {
+                        return Err(format!(
+                            "set '{}' of part '{}' conflicts with another part",
+                            set.name, name
+                        ))
+                    }
+                    Some(_) => {}
+                    None => sets.push(set.clone()),
+                }
+            }
+        }
+
+        // the composite only has a declared Γ if every part has one
+        let mut tape_alphabet: Option<Vec<char>> = Some(Vec::new());
+        for (_, machine) in &self.parts {
+            tape_alphabet = match (tape_alphabet, &machine.tape_alphabet) {
+                (Some(mut gamma), Some(part)) => {
+                    for symbol in part {
+                        if !gamma.contains(symbol) {
+                            gamma.push(*symbol);
+                        }
+                    }
+                    Some(gamma)
+                }
+                _ => None,
+            };
+        }
+
+        // halting states that are linked elsewhere, by their flat name
+        let mut aliases: HashMap<String, String> = HashMap::new();
+        let mut accepts = false;
+        for link in &self.links {
+            let Some(machine) = self.part(&link.from) else {
+                return Err(format!("unknown part '{}'", link.from));
+            };
+            let target = match &link.to {
+                Target::Part(name) => match self.part(name) {
+                    Some(next) => flat_name(name, &next.states.initial_state),
+                    None => return Err(format!("unknown part '{}'", name)),
+                },
+                Target::Accept => {
+                    accepts = true;
+                    ACCEPT.to_string()
+                }
+                Target::Reject => REJECT.to_string(),
+            };
+            let halting = match &link.on {
+                Some(state) => vec![state.clone()],
+                None => machine.states.final_states.clone(),
+            };
+            for state in halting {
+                if machine.transitions.iter().any(|t| t.lhs.state == state) {
+                    return Err(format!(
+                        "state '{}' of part '{}' is linked but has rules of its own",
+                        state, link.from
+                    ));
+                }
+                let name = flat_name(&link.from, &state);
+                if aliases.insert(name.clone(), target.clone()).is_some() {
+                    return Err(format!("state '{}' is linked twice", name));
+                }
+            }
+        }
+        let resolve = |name: String| -> Result<String, String> {
+            let mut current = name;
+            for _ in 0..=aliases.len() {
+                match aliases.get(&current) {
+                    Some(next) => current = next.clone(),
+                    None => return Ok(current),
+                }
+            }
+            Err(format!("links through '{}' form a loop", current))
+        };
+
+        let mut transitions: Vec<TransitionFunction> = Vec::new();
+        let mut final_states: Vec<String> = Vec::new();
+        for (name, machine) in &self.parts {
+            for t in &machine.transitions {
+                transitions.push(TransitionFunction {
+                    lhs: LHS {
+                        state: resolve(flat_name(name, &t.lhs.state))?,
+                        input: t.lhs.input.clone(),
+                    },
+                    rhs: RHS {
+                        state: resolve(flat_name(name, &t.rhs.state))?,
+                        replacement: t.rhs.replacement.clone(),
+                        direction: t.rhs.direction,
+                    },
+                });
+            }
+            // final states nobody continues from stay final
+            for state in &machine.states.final_states {
+                let flat = flat_name(name, state);
+                if !aliases.contains_key(&flat) && !final_states.contains(&flat) {
+                    final_states.push(flat);
+                }
+            }
+        }
+        if accepts {
+            final_states.push(ACCEPT.to_string());
+        }
+
+        Ok(Machine {
+            transitions,
+            states: KeyStates {
+                initial_state: resolve(flat_name(&start, &start_machine.states.initial_state))?,
+                final_states,
+            },
+            tracks: first.tracks,
+            tape_mode: start_machine.tape_mode,
+            sets,
+            input_alphabet: start_machine.input_alphabet.clone(),
+            tape_alphabet,
+        })
+    }
+}
+
+fn flat_name(part: &str, state: &str) -> String {
+    format!("{}.{}", part, state)
+}
+
+/// Reads a composition file; part paths are relative to the file.
+pub fn read_composition(path: &str) -> Result<Machine, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut composition = Composition::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let error = |message: String| format!("{}: line {}: {}", path, number + 1, message);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("part ") {
+            let Some((name, file)) = rest.split_once('=') else {
+                return Err(error(String::from("expected 'part <name> = <file>'")));
+            };
+            let file = base.join(file.trim());
+            let machine = definition::read_machine(&file.to_string_lossy())
+                .map_err(|e| error(e.to_string()))?;
+            composition.add(name.trim(), machine).map_err(error)?;
+        } else if let Some(name) = line.strip_prefix("start ") {
+            composition.start(name.trim());
+        } else if let Some((from, to)) = line.split_once("->") {
+            let (part, on) = match from.trim().split_once('.') {
+                Some((part, state)) => (part, Some(state)),
+                None => (from.trim(), None),
+            };
+            let to = match to.trim() {
+                ACCEPT => Target::Accept,
+                REJECT => Target::Reject,
+                name => Target::Part(name.to_string()),
+            };
+            composition.link(part, on, to);
+        } else {
+            return Err(error(format!("can't understand '{}'", line)));
+        }
+    }
+    composition.build().map_err(|e| format!("{}: {}", path, e))
+}
@@ -0,0 +1,309 @@
+use crate::definition::format_function;
+use crate::engine::{Run, Status, Step};
+use crate::{get_input, Machine};
+use colored::*;
+use std::io::{self, Write};
+
+// Full-screen debugger over the engine. The screen is redrawn after every
+// command; commands are read a line at a time like everywhere else.
+
+/// Cells shown on either side of the head.
+const TAPE_RADIUS: i64 = 15;
+/// Rules shown around the active one.
+const RULES_SHOWN: usize = 15;
+/// Steps `continue` and `run` take before pausing, so that machines that
+/// never halt don't hang the debugger.
+const RUN_LIMIT: usize = 1_000_000;
+
+const HELP: &str = "\
+s, step [n]        take one step, or n steps (stops at breakpoints)
+u, back [n]        undo one step, or n steps
+g, goto <n>        jump backwards or forwards to step n
+c, continue        run until a breakpoint is hit or the machine halts
+r, run             run until the machine halts, ignoring breakpoints
+b state <q>        break when the machine enters state q
+b symbol <a>       break when the head reads a
+b step <n>         break once n steps have been taken
+d <n>, d           delete breakpoint n, or every breakpoint
+h, help            show this help
+q, quit            leave the debugger";
+
+pub enum Breakpoint {
+    State(String),
+    Symbol(String),
+    Step(usize),
+}
+
+impl Breakpoint {
+    fn parse(kind: &str, value: &str) -> Result<Breakpoint, String> {
+        match kind {
+            "state" => Ok(Breakpoint::State(value.to_string())),
+            "symbol" => Ok(Breakpoint::Symbol(value.replace("blank", "□"))),
+            "step" => value
+                .parse()
+                .map(Breakpoint::Step)
+                .map_err(|_| format!("'{}' is not a step number", value)),
+            _ => Err(format!("unknown breakpoint kind '{}'", kind)),
+        }
+    }
+
+    fn hit(&self, run: &Run) -> bool {
+        match self {
+            Breakpoint::State(state) => &run.state == state,
+            // on multi-track machines a single symbol matches any track
+            Breakpoint::Symbol(symbol) => {
+                run.current() == symbol
+                    || (symbol.chars().count() == 1 && run.current().contains(symbol.as_str()))
+            }
+            Breakpoint::Step(step) => run.steps == *step,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Breakpoint::State(state) => format!("state {}", state),
+            Breakpoint::Symbol(symbol) => format!("symbol {}", symbol),
+            Breakpoint::Step(step) => format!("step {}", step),
+        }
+    }
+}
+
+pub struct Debugger<'m> {
+    run: Run<'m>,
+    breakpoints: Vec<Breakpoint>,
+    last: Option<Step>,
+    message: String,
+}
+
+impl<'m> Debugger<'m> {
+    pub fn new(machine: &'m Machine, input: &str) -> Debugger<'m> {
+        Debugger {
+            run: Run::with_history(machine, input),
+            breakpoints: Vec::new(),
+            last: None,
+            message: String::from("Type 'h' for help"),
+        }
+    }
+
+    fn step(&mut self) -> bool {
+        match self.run.step() {
+            Some(step) => {
+                self.last = Some(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes up to `count` steps, stopping early at a breakpoint or when the
+    /// machine halts.
+    fn advance(&mut self, count: usize, use_breakpoints: bool) {
+        for _ in 0..count {
+            if !self.step() {
+                break;
+            }
+            if use_breakpoints {
+                if let Some(index) = self.breakpoints.iter().position(|b| b.hit(&self.run)) {
+                    self.message = format!(
+                        "Breakpoint {} hit ({})",
+                        index + 1,
+                        self.breakpoints[index].describe()
+                    );
+                    return;
+                }
+            }
+        }
+        if self.run.status == Status::Running && count == RUN_LIMIT {
+            self.message = format!("Paused after {} steps", RUN_LIMIT);
+        }
+    }
+
+    fn back(&mut self, count: usize) {
+        for _ in 0..count {
+            if !self.run.back() {
+                self.message = String::from("Already at the first step");
+                break;
+            }
+        }
+        self.last = None;
+    }
+
+    /// Runs one command. Returns false once the user quits.
+    pub fn command(&mut self, line: &str) -> bool {
+        self.message.clear();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["s"] | ["step"] => self.advance(1, true),
+            ["s", n] | ["step", n] => match n.parse() {
+                Ok(n) => self.advance(n, true),
+                Err(_) => self.message = format!("'{}' is not a number of steps", n),
+            },
+            ["u"] | ["back"] => self.back(1),
+            ["u", n] | ["back", n] => match n.parse() {
+                Ok(n) => self.back(n),
+                Err(_) => self.message = format!("'{}' is not a number of steps", n),
+            },
+            ["g", n] | ["goto", n] => match n.parse() {
+                Ok(n) => {
+                    self.run.seek(n);
+                    self.last = None;
+                    if self.run.steps != n {
+                        self.message = format!("The machine halts at step {}", self.run.steps);
+                    }
+                }
+                Err(_) => self.message = format!("'{}' is not a step number", n),
+            },
+            ["c"] | ["continue"] => self.advance(RUN_LIMIT, true),
+            ["r"] | ["run"] => self.advance(RUN_LIMIT, false),
+            ["b", kind, value] | ["break", kind, value] => match Breakpoint::parse(kind, value) {
+                Ok(breakpoint) => {
+                    self.message = format!("Breakpoint {} set", self.breakpoints.len() + 1);
+                    self.breakpoints.push(breakpoint);
+                }
+                Err(e) => self.message = e,
+            },
+            ["d"] | ["delete"] => {
+                self.breakpoints.clear();
+                self.message = String::from("Deleted every breakpoint");
+            }
+            ["d", n] | ["delete", n] => match n.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= self.breakpoints.len() => {
+                    self.breakpoints.remove(n - 1);
+                    self.message = format!("Deleted breakpoint {}", n);
+                }
+                _ => self.message = format!("There is no breakpoint '{}'", n),
+            },
+            ["h"] | ["help"] => self.message = HELP.to_string(),
+            ["q"] | ["quit"] => return false,
+            _ => self.message = format!("Unknown command '{}', type 'h' for help", line.trim()),
+        }
+        if self.run.status != Status::Running && self.message.is_empty() {
+            self.message = String::from("The machine has halted");
+        }
+        true
+    }
+
+    pub fn render(&self) -> String {
+        let run = &self.run;
+        let machine = run.machine;
+        let mut screen = String::from("\x1B[2J\x1B[H");
+        let status = match run.status {
+            Status::Running => "running".normal(),
+            Status::Accepted => "accepted".green(),
+            Status::Rejected => "rejected".red(),
+            Status::Crashed => "crashed".red(),
+        };
+        screen.push_str(&format!(
+            "{}   step {}   state {}   {}\n\n",
+            "Turing Machine Debugger".bold(),
+            run.steps,
+            run.state.bold(),
+            status
+        ));
+
+        // tape window centred on the head
+        let left_end = if machine.tape_mode.is_one_way() {
+            0
+        } else {
+            i64::MIN
+        };
+        let positions: Vec<i64> = (run.head - TAPE_RADIUS..=run.head + TAPE_RADIUS).collect();
+        let mut head_line = String::from("HEAD ");
+        let mut track_lines: Vec<String> = vec![String::from("TAPE "); machine.tracks];
+        for &position in &positions {
+            let before_end = position < left_end;
+            head_line.push_str(if position == run.head { " ▼ " } else { "   " });
+            for (track, line) in track_lines.iter_mut().enumerate() {
+                let text = if before_end {
+                    if position == left_end - 1 {
+                        String::from("  ⊢")
+                    } else {
+                        String::from("   ")
+                    }
+                } else {
+                    let c = run.cell(position).chars().nth(track).unwrap_or('□');
+                    format!(" {} ", c)
+                };
+                if position == run.head {
+                    line.push_str(&text.reversed().to_string());
+                } else {
+                    line.push_str(&text);
+                }
+            }
+        }
+        screen.push_str(&head_line);
+        screen.push('\n');
+        for line in track_lines {
+            screen.push_str(&line);
+            screen.push('\n');
+        }
+        screen.push_str(&format!("Head position: {}\n\n", run.head));
+
+        let next = run.next_rule();
+        match next {
+            Some(rule) => screen.push_str(&format!(
+                "Next rule: {}\n",
+                format_function(&machine.transitions[rule])
+            )),
+            None => screen.push_str("Next rule: none\n"),
+        }
+        if let Some(step) = &self.last {
+            screen.push_str(&format!(
+                "Last step: read '{}', wrote '{}' at position {}\n",
+                step.read, step.written, step.position
+            ));
+        }
+
+        screen.push_str("\nRules:\n");
+        let active = next
+            .or(self.last.as_ref().map(|step| step.rule))
+            .unwrap_or(0);
+        let first = active
+            .saturating_sub(RULES_SHOWN / 2)
+            .min(machine.transitions.len().saturating_sub(RULES_SHOWN));
+        for (index, function) in machine
+            .transitions
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(RULES_SHOWN)
+        {
+            let line = format!("{:>4}  {}", index + 1, format_function(function));
+            if Some(index) == next {
+                screen.push_str(&format!("{}\n", format!("▶{}", line).reversed()));
+            } else {
+                screen.push_str(&format!(" {}\n", line));
+            }
+        }
+
+        if !self.breakpoints.is_empty() {
+            let list: Vec<String> = self
+                .breakpoints
+                .iter()
+                .enumerate()
+                .map(|(index, breakpoint)| format!("{}) {}", index + 1, breakpoint.describe()))
+                .collect();
+            screen.push_str(&format!("\nBreakpoints: {}\n", list.join("   ")));
+        }
+        screen.push_str(&format!("\n{}\n", self.message));
+        screen
+    }
+}
+
+/// Runs the debugger on one input until the user quits.
+pub fn debug(machine: &Machine, input: &str) {
+    let mut debugger = Debugger::new(machine, input);
+    loop {
+        print!("{}(debug) ", debugger.render());
+        io::stdout().flush().expect("failed to flush");
+        let line = get_input();
+        if line.is_empty() || !debugger.command(&line) {
+            break;
+        }
+    }
+    match debugger.run.status {
+        Status::Accepted => println!("{}", "Success".green()),
+        Status::Running => println!("Stopped after {} steps", debugger.run.steps),
+        _ => println!("{}", "Failure".red()),
+    }
+}
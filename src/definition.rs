@@ -0,0 +1,367 @@
+use crate::rules::{self, SymbolSet};
+use crate::tape::TapeMode;
+use crate::{KeyStates, Machine, TransitionFunction, LHS, RHS};
+use csv::Writer;
+use std::fs;
+use std::io;
+
+// Machine definition files (.tm) hold everything needed to run a machine:
+//
+//   # adds two unary numbers separated by a 0
+//   name: add
+//   description: adds two unary numbers
+//   tracks: 1
+//   tape: two-way
+//   initial: q0
+//   final: q4
+//   input_alphabet: {0, 1}
+//   tape_alphabet: {0, 1, □}
+//   digit = {0..9}
+//   δ(q0,1)=(q0,1,R)
+//   test: 11011 -> accept 11110
+//
+// Lines starting with '#' are comments, 'key: value' lines set properties,
+// 'name = {...}' lines declare symbol sets and everything else is a rule.
+// 'name:', 'description:' and 'test:' (see suite.rs) are metadata and don't
+// change the machine.
+
+/// Splits `text` on `separator`, ignoring separators nested in (), [] or {}.
+pub fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Parses a rule written as `δ(q1,a)=(q2,b,L)`; the leading δ is optional,
+/// spaces are ignored and 'blank' can be used instead of □.
+pub fn parse_function(text: &str) -> Result<TransitionFunction, String> {
+    let text = text
+        .replace([' ', '\t', '\r', '\n'], "")
+        .replace("blank", "□");
+    let text = text.strip_prefix('δ').unwrap_or(&text);
+    let Some((lhs, rhs)) = text.split_once(")=(") else {
+        return Err(format!("'{}' is not a transition function", text));
+    };
+    let lhs_parts = split_top_level(lhs.strip_prefix('(').unwrap_or(lhs), ',');
+    let rhs_parts = split_top_level(rhs.strip_suffix(')').unwrap_or(rhs), ',');
+    if lhs_parts.len() != 2 || rhs_parts.len() != 3 {
+        return Err(format!("'{}' is not a transition function", text));
+    }
+    let direction = match rhs_parts[2].to_uppercase().as_str() {
+        "L" => 'L',
+        "R" => 'R',
+        _ => return Err(format!("invalid direction '{}'", rhs_parts[2])),
+    };
+    if lhs_parts
+        .iter()
+        .chain(&rhs_parts)
+        .any(|part| part.is_empty())
+    {
+        return Err(format!("'{}' has an empty field", text));
+    }
+    Ok(TransitionFunction {
+        lhs: LHS {
+            state: lhs_parts[0].to_string(),
+            input: lhs_parts[1].to_string(),
+        },
+        rhs: RHS {
+            state: rhs_parts[0].to_string(),
+            replacement: rhs_parts[1].to_string(),
+            direction,
+        },
+    })
+}
+
+/// Tells whether a line of a definition is a set declaration rather than a rule.
+pub fn is_set_declaration(line: &str) -> bool {
+    let line = line.trim();
+    // 'key: value' properties may contain '=' too, e.g. 'test: 1 -> accept steps=2'
+    !line.starts_with('(')
+        && !line.starts_with('δ')
+        && line
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.contains(':'))
+}
+
+pub fn parse_machine(text: &str) -> Result<Machine, String> {
+    let mut tracks: usize = 1;
+    let mut tape_mode = TapeMode::TwoWay;
+    let mut initial: Option<String> = None;
+    let mut finals: Vec<String> = Vec::new();
+    let mut sets: Vec<SymbolSet> = Vec::new();
+    let mut input_alphabet: Option<Vec<char>> = None;
+    let mut tape_alphabet: Option<Vec<char>> = None;
+    let mut functions: Vec<(usize, TransitionFunction)> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let number = number + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if is_set_declaration(line) {
+            let set = rules::parse_set(line).map_err(|e| format!("line {}: {}", number, e))?;
+            sets.push(set);
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if !key.contains('(') {
+                let value = value.trim();
+                match key.trim().to_lowercase().as_str() {
+                    "tracks" => {
+                        tracks = value
+                            .parse()
+                            .ok()
+                            .filter(|&t| t > 0)
+                            .ok_or(format!("line {}: invalid number of tracks", number))?
+                    }
+                    "tape" => {
+                        tape_mode = TapeMode::from_name(value)
+                            .ok_or(format!("line {}: unknown tape mode '{}'", number, value))?
+                    }
+                    "initial" => initial = Some(value.to_string()),
+                    "name" | "description" | "test" => {}
+                    "input_alphabet" | "σ" => {
+                        input_alphabet = Some(
+                            rules::parse_symbols(value)
+                                .map_err(|e| format!("line {}: {}", number, e))?,
+                        )
+                    }
+                    "tape_alphabet" | "γ" => {
+                        tape_alphabet = Some(
+                            rules::parse_symbols(value)
+                                .map_err(|e| format!("line {}: {}", number, e))?,
+                        )
+                    }
+                    "final" => finals.extend(
+                        value
+                            .split(',')
+                            .map(|s| s.trim().to_string())
+                            .filter(|s| !s.is_empty()),
+                    ),
+                    other => return Err(format!("line {}: unknown property '{}'", number, other)),
+                }
+                continue;
+            }
+        }
+        let function = parse_function(line).map_err(|e| format!("line {}: {}", number, e))?;
+        functions.push((number, function));
+    }
+
+    for (number, function) in &functions {
+        rules::check(function, tracks, &sets).map_err(|e| format!("line {}: {}", number, e))?;
+    }
+    let Some(initial_state) = initial else {
+        return Err(String::from("missing 'initial:' state"));
+    };
+    Ok(Machine {
+        transitions: functions.into_iter().map(|(_, f)| f).collect(),
+        states: KeyStates {
+            initial_state,
+            final_states: finals,
+        },
+        tracks,
+        tape_mode,
+        sets,
+        input_alphabet,
+        tape_alphabet: rules::with_blank(tape_alphabet),
+    })
+}
+
+/// The values of every `key: value` property in a definition.
+pub fn properties<'t>(text: &'t str, key: &'t str) -> impl Iterator<Item = &'t str> {
+    text.lines().filter_map(move |line| {
+        let (k, value) = line.split_once(':')?;
+        (k.trim().eq_ignore_ascii_case(key) && !k.contains('(')).then(|| value.trim())
+    })
+}
+
+/// The value of a property, e.g. the name of the machine.
+pub fn property<'t>(text: &'t str, key: &'t str) -> Option<&'t str> {
+    properties(text, key).next()
+}
+
+pub fn read_machine(path: &str) -> io::Result<Machine> {
+    let text = fs::read_to_string(path)?;
+    parse_machine(&text).map_err(|e| io::Error::other(format!("{}: {}", path, e)))
+}
+
+pub fn format_function(function: &TransitionFunction) -> String {
+    format!(
+        "δ({},{})=({},{},{})",
+        function.lhs.state,
+        function.lhs.input,
+        function.rhs.state,
+        function.rhs.replacement,
+        function.rhs.direction
+    )
+}
+
+pub fn format_set(set: &SymbolSet) -> String {
+    format!("{} = {}", set.name, rules::format_symbols(&set.symbols))
+}
+
+/// Writes a machine in the definition file format.
+pub fn write_machine(machine: &Machine) -> String {
+    let mut text = String::new();
+    text.push_str(&format!("tracks: {}\n", machine.tracks));
+    text.push_str(&format!("tape: {}\n", machine.tape_mode.name()));
+    text.push_str(&format!("initial: {}\n", machine.states.initial_state));
+    if !machine.states.final_states.is_empty() {
+        text.push_str(&format!(
+            "final: {}\n",
+            machine.states.final_states.join(", ")
+        ));
+    }
+    if let Some(sigma) = &machine.input_alphabet {
+        text.push_str(&format!(
+            "input_alphabet: {}\n",
+            rules::format_symbols(sigma)
+        ));
+    }
+    if let Some(gamma) = &machine.tape_alphabet {
+        text.push_str(&format!(
+            "tape_alphabet: {}\n",
+            rules::format_symbols(gamma)
+        ));
+    }
+    for set in &machine.sets {
+        text.push_str(&format_set(set));
+        text.push('\n');
+    }
+    text.push('\n');
+    for function in &machine.transitions {
+        text.push_str(&format_function(function));
+        text.push('\n');
+    }
+    text
+}
+
+/// Writes a machine's rules as CSV. CSV only holds the rules, so tracks,
+/// states and alphabets are asked for again when the file is loaded, and
+/// named sets are written inline.
+pub fn write_csv(machine: &Machine) -> io::Result<String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record([
+        "lhs_state",
+        "input",
+        "rhs_state",
+        "replacement",
+        "direction",
+    ])?;
+    for function in &machine.transitions {
+        writer.write_record([
+            function.lhs.state.as_str(),
+            rules::inline_sets(&function.lhs.input, &machine.sets).as_str(),
+            function.rhs.state.as_str(),
+            function.rhs.replacement.as_str(),
+            function.rhs.direction.to_string().as_str(),
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    String::from_utf8(bytes).map_err(io::Error::other)
+}
+
+/// Tells whether a path names a CSV file, whatever the case of its extension.
+pub fn is_csv(path: &str) -> bool {
+    path.to_lowercase().ends_with(".csv")
+}
+
+/// Saves a machine as CSV if the path ends in .csv, otherwise as a machine
+/// definition file.
+pub fn save_machine(path: &str, machine: &Machine) -> io::Result<()> {
+    let text = if is_csv(path) {
+        write_csv(machine)?
+    } else {
+        write_machine(machine)
+    };
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_are_parsed() {
+        let machine = parse_machine(
+            "# a comment
+             name: pairs
+             tracks: 2
+             tape: one-way-stay
+             initial: q0
+             final: q1, q2
+             input_alphabet: {a, b}
+             tape_alphabet: {a, b}
+             ab = {a, b}
+             δ(q0,[ab]blank)=(q1,?,R)
+             (q0, ??) = (q2, ab, l)
+             test: ab ba -> accept",
+        )
+        .unwrap();
+        assert_eq!(machine.tracks, 2);
+        assert_eq!(machine.tape_mode, TapeMode::OneWayStay);
+        assert_eq!(machine.states.initial_state, "q0");
+        assert_eq!(machine.states.final_states, ["q1", "q2"]);
+        assert_eq!(machine.sets[0].symbols, ['a', 'b']);
+        assert_eq!(machine.input_alphabet, Some(vec!['a', 'b']));
+        assert_eq!(machine.tape_alphabet, Some(vec!['a', 'b', '□']));
+        assert_eq!(
+            machine
+                .transitions
+                .iter()
+                .map(format_function)
+                .collect::<Vec<_>>(),
+            ["δ(q0,[ab]□)=(q1,?,R)", "δ(q0,??)=(q2,ab,L)"]
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |text: &str| parse_machine(text).err().unwrap();
+        assert_eq!(
+            error("initial: q0\nspeed: 3"),
+            "line 2: unknown property 'speed'"
+        );
+        assert_eq!(error("tracks: 0"), "line 1: invalid number of tracks");
+        assert_eq!(
+            error("tape: circular"),
+            "line 1: unknown tape mode 'circular'"
+        );
+        assert_eq!(
+            error("initial: q0\n\nδ(q0,a)=(q1,a,X)"),
+            "line 3: invalid direction 'X'"
+        );
+        assert_eq!(
+            error("initial: q0\nδ(q0,a)(q1,a,R)"),
+            "line 2: '(q0,a)(q1,a,R)' is not a transition function"
+        );
+        assert_eq!(
+            error("tracks: 2\ninitial: q0\nδ(q0,a)=(q1,ab,R)"),
+            "line 3: input 'a' has 1 tracks, expected 2"
+        );
+        assert_eq!(error("δ(q0,a)=(q1,a,R)"), "missing 'initial:' state");
+    }
+
+    #[test]
+    fn written_definitions_read_back_the_same() {
+        let machine = parse_machine(include_str!("../example.tm")).unwrap();
+        let text = write_machine(&machine);
+        assert_eq!(write_machine(&parse_machine(&text).unwrap()), text);
+    }
+}
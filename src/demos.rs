@@ -0,0 +1,94 @@
+use crate::definition;
+use crate::suite::{self, Suite};
+use crate::Machine;
+
+// Demos are ordinary definition files compiled into the binary. Each file
+// names and describes itself with 'name:' and 'description:' properties and
+// lists 'test:' cases that show it works. The order here is the order of the
+// '-demo <n>' indexes.
+
+const SOURCES: [&str; 16] = [
+    include_str!("demos/translator.tm"),
+    include_str!("demos/anbn.tm"),
+    include_str!("demos/copy.tm"),
+    include_str!("demos/tracks.tm"),
+    include_str!("demos/multiply.tm"),
+    include_str!("demos/increment.tm"),
+    include_str!("demos/decrement.tm"),
+    include_str!("demos/addition.tm"),
+    include_str!("demos/palindrome.tm"),
+    include_str!("demos/anbncn.tm"),
+    include_str!("demos/ww.tm"),
+    include_str!("demos/binary.tm"),
+    include_str!("demos/beaver2.tm"),
+    include_str!("demos/beaver3.tm"),
+    include_str!("demos/beaver4.tm"),
+    include_str!("demos/utm.tm"),
+];
+
+pub struct Demo {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The definition file the demo is built from.
+    pub source: &'static str,
+}
+
+impl Demo {
+    pub fn machine(&self) -> Machine {
+        definition::parse_machine(self.source)
+            .unwrap_or_else(|e| panic!("demo '{}' is invalid: {}", self.name, e))
+    }
+
+    pub fn suite(&self) -> Suite {
+        suite::definition_suite(self.source, self.machine().tracks)
+            .unwrap_or_else(|e| panic!("demo '{}' has a bad test: {}", self.name, e))
+    }
+}
+
+pub fn demos() -> Vec<Demo> {
+    SOURCES
+        .iter()
+        .map(|source| Demo {
+            name: definition::property(source, "name").unwrap_or(""),
+            description: definition::property(source, "description").unwrap_or(""),
+            source,
+        })
+        .collect()
+}
+
+/// Finds a demo by name or by its index in the list.
+pub fn find(key: &str) -> Option<Demo> {
+    let mut demos = demos();
+    let index = match key.parse::<usize>() {
+        Ok(index) => index,
+        Err(_) => demos.iter().position(|demo| demo.name == key)?,
+    };
+    (index < demos.len()).then(|| demos.swap_remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_demo_passes_its_tests() {
+        for demo in demos() {
+            let machine = demo.machine();
+            let suite = demo.suite();
+            assert!(!suite.cases.is_empty(), "demo '{}' has no tests", demo.name);
+            for (text, case) in &suite.cases {
+                if let Err(e) = suite::run_case(&machine, case, suite.limit) {
+                    panic!("demo '{}': {}\n{}", demo.name, text, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn demos_are_found_by_name_or_index() {
+        assert_eq!(find("anbn").map(|demo| demo.name), Some("anbn"));
+        assert_eq!(find("1").map(|demo| demo.name), Some("anbn"));
+        assert!(find("16").is_none());
+        assert!(find("nothing").is_none());
+    }
+}
@@ -0,0 +1,38 @@
+name: addition
+description: adds two binary numbers (e.g. input: 101+11)
+tracks: 1
+tape: two-way
+initial: q0
+final: q6
+input_alphabet: {0, 1, +}
+tape_alphabet: {0, 1, +, □}
+
+# go to the end of the second number
+δ(q0,0)=(q0,0,R)
+δ(q0,1)=(q0,1,R)
+δ(q0,+)=(q0,+,R)
+δ(q0,□)=(q1,□,L)
+# take one from the second number
+δ(q1,0)=(q1,1,L)
+δ(q1,1)=(q2,0,L)
+# back to the first number and add one to it
+δ(q2,0)=(q2,0,L)
+δ(q2,1)=(q2,1,L)
+δ(q2,+)=(q3,+,L)
+δ(q3,1)=(q3,0,L)
+δ(q3,0)=(q4,1,R)
+δ(q3,□)=(q4,1,R)
+δ(q4,0)=(q4,0,R)
+δ(q4,1)=(q4,1,R)
+δ(q4,+)=(q0,+,R)
+# the second number was zero and is now all ones, erase it
+δ(q1,+)=(q5,□,R)
+δ(q5,1)=(q5,□,R)
+δ(q5,□)=(q6,□,L)
+
+test: 101+11 -> accept 1000
+test: 1+1 -> accept 10
+test: 111+1 -> accept 1000
+test: 0+0 -> accept 0
+test: 1101+0 -> accept 1101
+test: 0+110 -> accept 110
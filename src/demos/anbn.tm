@@ -0,0 +1,29 @@
+name: anbn
+description: accepts strings in form of a(n)b(n)
+tracks: 1
+tape: two-way
+initial: q0
+final: q4
+input_alphabet: {a, b}
+tape_alphabet: {a, b, x, y, □}
+
+# mark the leftmost a with x and the matching b with y
+δ(q0,a)=(q1,x,R)
+δ(q1,a)=(q1,a,R)
+δ(q1,y)=(q1,y,R)
+δ(q1,b)=(q2,y,L)
+δ(q2,y)=(q2,y,L)
+δ(q2,a)=(q2,a,L)
+δ(q2,x)=(q0,x,R)
+# no a left, only ys may follow
+δ(q0,y)=(q3,y,R)
+δ(q3,y)=(q3,y,R)
+δ(q3,□)=(q4,□,L)
+
+test: ab -> accept xy
+test: aaabbb -> accept xxxyyy
+test: ε -> reject
+test: aab -> reject
+test: abb -> reject
+test: abab -> reject
+test: ba -> reject
@@ -0,0 +1,36 @@
+name: anbncn
+description: accepts strings in form of a(n)b(n)c(n)
+tracks: 1
+tape: two-way
+initial: q0
+final: q6
+input_alphabet: {a, b, c}
+tape_alphabet: {a, b, c, x, y, z, □}
+
+# cross off one a, one b and one c
+δ(q0,a)=(q1,x,R)
+δ(q1,a)=(q1,a,R)
+δ(q1,y)=(q1,y,R)
+δ(q1,b)=(q2,y,R)
+δ(q2,b)=(q2,b,R)
+δ(q2,z)=(q2,z,R)
+δ(q2,c)=(q3,z,L)
+δ(q3,a)=(q3,a,L)
+δ(q3,b)=(q3,b,L)
+δ(q3,y)=(q3,y,L)
+δ(q3,z)=(q3,z,L)
+δ(q3,x)=(q0,x,R)
+# no a left, only crossed off symbols may follow
+δ(q0,y)=(q4,y,R)
+δ(q4,y)=(q4,y,R)
+δ(q4,z)=(q5,z,R)
+δ(q5,z)=(q5,z,R)
+δ(q5,□)=(q6,□,L)
+
+test: abc -> accept xyz
+test: aabbcc -> accept xxyyzz
+test: ε -> reject
+test: aabbc -> reject
+test: abcabc -> reject
+test: acb -> reject
+test: aabc -> reject
@@ -0,0 +1,14 @@
+name: beaver2
+description: the 2-state busy beaver, run it on an empty tape
+tracks: 1
+tape: two-way
+initial: A
+final: H
+tape_alphabet: {1, □}
+
+δ(A,□)=(B,1,R)
+δ(A,1)=(B,1,L)
+δ(B,□)=(A,1,L)
+δ(B,1)=(H,1,R)
+
+test: ε -> accept 1111 steps=6
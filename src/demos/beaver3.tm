@@ -0,0 +1,16 @@
+name: beaver3
+description: the 3-state busy beaver, run it on an empty tape
+tracks: 1
+tape: two-way
+initial: A
+final: H
+tape_alphabet: {1, □}
+
+δ(A,□)=(B,1,R)
+δ(A,1)=(H,1,R)
+δ(B,□)=(C,□,R)
+δ(B,1)=(B,1,R)
+δ(C,□)=(C,1,L)
+δ(C,1)=(A,1,L)
+
+test: ε -> accept 111111 steps=14
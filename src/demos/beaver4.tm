@@ -0,0 +1,18 @@
+name: beaver4
+description: the 4-state busy beaver, run it on an empty tape
+tracks: 1
+tape: two-way
+initial: A
+final: H
+tape_alphabet: {1, □}
+
+δ(A,□)=(B,1,R)
+δ(A,1)=(B,1,L)
+δ(B,□)=(A,1,L)
+δ(B,1)=(C,□,L)
+δ(C,□)=(H,1,R)
+δ(C,1)=(D,1,L)
+δ(D,□)=(D,1,R)
+δ(D,1)=(A,□,R)
+
+test: ε -> accept 1□111111111111 steps=107
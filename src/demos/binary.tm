@@ -0,0 +1,37 @@
+name: binary
+description: converts a unary number to binary (e.g. input: 11111)
+tracks: 1
+tape: two-way
+initial: q0
+final: q8, q9
+input_alphabet: {1}
+tape_alphabet: {0, 1, #, x, □}
+
+# start a counter at 0 left of the input, separated by #
+δ(q0,1)=(q1,1,L)
+δ(q0,□)=(q9,0,R)
+δ(q1,□)=(q2,#,L)
+δ(q2,□)=(q3,0,R)
+# cross off a 1 of the input...
+δ(q3,0)=(q3,0,R)
+δ(q3,1)=(q3,1,R)
+δ(q3,#)=(q4,#,R)
+δ(q4,x)=(q4,x,R)
+δ(q4,1)=(q5,x,L)
+δ(q5,x)=(q5,x,L)
+δ(q5,#)=(q6,#,L)
+# ...and add one to the counter
+δ(q6,1)=(q6,0,L)
+δ(q6,0)=(q3,1,R)
+δ(q6,□)=(q3,1,R)
+# every 1 is crossed off, erase the input
+δ(q4,□)=(q7,□,L)
+δ(q7,x)=(q7,□,L)
+δ(q7,#)=(q8,□,L)
+
+test: 11111 -> accept 101
+test: 1 -> accept 1
+test: 11 -> accept 10
+test: 111111 -> accept 110
+test: 1111111111 -> accept 1010
+test: ε -> accept 0
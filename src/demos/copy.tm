@@ -0,0 +1,22 @@
+name: copy
+description: copies strings of '1'
+tracks: 1
+tape: two-way
+initial: q0
+final: q3
+input_alphabet: {1}
+tape_alphabet: {1, x, □}
+
+# mark every 1 with x
+δ(q0,1)=(q0,x,R)
+δ(q0,□)=(q1,□,L)
+# turn the rightmost x back into 1 and write a copy at the end
+δ(q1,1)=(q1,1,L)
+δ(q1,□)=(q3,□,R)
+δ(q1,x)=(q2,1,R)
+δ(q2,1)=(q2,1,R)
+δ(q2,□)=(q1,1,L)
+
+test: 1 -> accept 11
+test: 111 -> accept 111111
+test: ε -> accept ε
@@ -0,0 +1,22 @@
+name: decrement
+description: subtracts one from a binary number, rejecting zero (e.g. input: 1100)
+tracks: 1
+tape: two-way
+initial: q0
+final: q2
+input_alphabet: {0, 1}
+tape_alphabet: {0, 1, □}
+
+# move to the last digit
+δ(q0,0)=(q0,0,R)
+δ(q0,1)=(q0,1,R)
+δ(q0,□)=(q1,□,L)
+# borrow from the left, there is nothing to borrow from in zero
+δ(q1,0)=(q1,1,L)
+δ(q1,1)=(q2,0,L)
+
+test: 1100 -> accept 1011
+test: 1000 -> accept 0111
+test: 1 -> accept 0
+test: 000 -> reject
+test: ε -> reject
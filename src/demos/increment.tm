@@ -0,0 +1,22 @@
+name: increment
+description: adds one to a binary number (e.g. input: 1011)
+tracks: 1
+tape: two-way
+initial: q0
+final: q2
+input_alphabet: {0, 1}
+tape_alphabet: {0, 1, □}
+
+# move to the last digit
+δ(q0,0)=(q0,0,R)
+δ(q0,1)=(q0,1,R)
+δ(q0,□)=(q1,□,L)
+# carry the one to the left
+δ(q1,1)=(q1,0,L)
+δ(q1,0)=(q2,1,L)
+δ(q1,□)=(q2,1,L)
+
+test: 1011 -> accept 1100
+test: 111 -> accept 1000
+test: 0 -> accept 1
+test: ε -> accept 1
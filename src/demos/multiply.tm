@@ -0,0 +1,45 @@
+name: multiply
+description: a turing machine for multiplication (e.g. input: 11*11)
+tracks: 1
+tape: two-way
+initial: q0
+final: q12
+input_alphabet: {1, *}
+tape_alphabet: {1, *, X, Y, □}
+
+# append a * after the second number and go back to its start
+δ(q0,1)=(q0,1,R)
+δ(q0,*)=(q1,*,R)
+δ(q1,1)=(q1,1,R)
+δ(q1,□)=(q2,*,L)
+δ(q2,1)=(q2,1,L)
+δ(q2,*)=(q3,*,R)
+# for every 1 of the second number (marked X)...
+δ(q3,X)=(q3,X,R)
+δ(q3,1)=(q4,X,L)
+δ(q3,*)=(q12,□,R)
+δ(q4,X)=(q4,X,L)
+δ(q4,*)=(q5,*,L)
+# ...copy the first number (marked Y) to the end
+δ(q5,Y)=(q5,Y,L)
+δ(q5,1)=(q6,Y,R)
+δ(q5,□)=(q11,□,R)
+δ(q6,Y)=(q6,Y,R)
+δ(q6,*)=(q7,*,R)
+δ(q7,1)=(q7,1,R)
+δ(q7,X)=(q7,X,R)
+δ(q7,*)=(q8,*,R)
+δ(q8,1)=(q8,1,R)
+δ(q8,□)=(q9,1,L)
+δ(q9,1)=(q9,1,L)
+δ(q9,*)=(q10,*,L)
+δ(q10,1)=(q10,1,L)
+δ(q10,X)=(q10,X,L)
+δ(q10,*)=(q5,*,L)
+# unmark the first number
+δ(q11,Y)=(q11,1,R)
+δ(q11,*)=(q3,*,R)
+
+test: 11*111 -> accept 11*XXX□111111
+test: 1*1 -> accept 1*X□1
+test: 111*11 -> accept 111*XX□111111
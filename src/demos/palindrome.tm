@@ -0,0 +1,37 @@
+name: palindrome
+description: accepts strings of 'a' & 'b' that read the same backwards (e.g. input: abba)
+tracks: 1
+tape: two-way
+initial: q0
+final: q6
+input_alphabet: {a, b}
+tape_alphabet: {a, b, □}
+
+# erase the first symbol and remember it
+δ(q0,a)=(q1,□,R)
+δ(q0,b)=(q2,□,R)
+δ(q0,□)=(q6,□,R)
+# go to the last symbol
+δ(q1,a)=(q1,a,R)
+δ(q1,b)=(q1,b,R)
+δ(q1,□)=(q3,□,L)
+δ(q2,a)=(q2,a,R)
+δ(q2,b)=(q2,b,R)
+δ(q2,□)=(q4,□,L)
+# it has to match the first one
+δ(q3,a)=(q5,□,L)
+δ(q3,□)=(q6,□,R)
+δ(q4,b)=(q5,□,L)
+δ(q4,□)=(q6,□,R)
+# back to the start
+δ(q5,a)=(q5,a,L)
+δ(q5,b)=(q5,b,L)
+δ(q5,□)=(q0,□,R)
+
+test: abba -> accept ε
+test: aba -> accept
+test: a -> accept
+test: ε -> accept
+test: ab -> reject
+test: abb -> reject
+test: babaab -> reject
@@ -0,0 +1,18 @@
+name: tracks
+description: checks two tracks of 'a' & 'b' and finds where tracks match
+tracks: 2
+tape: two-way
+initial: q0
+final: q1
+input_alphabet: {a, b}
+tape_alphabet: {a, b, 1, □}
+
+δ(q0,aa)=(q0,11,R)
+δ(q0,bb)=(q0,11,R)
+δ(q0,ab)=(q0,ab,R)
+δ(q0,ba)=(q0,ba,R)
+δ(q0,□□)=(q1,□□,L)
+
+test: aab abb -> accept 1a1 1b1
+test: ab ba -> accept ab ba
+test: ε ε -> accept ε ε
@@ -0,0 +1,16 @@
+name: translator
+description: translates every 'a' to 'b'
+tracks: 1
+tape: two-way
+initial: q0
+final: q1
+input_alphabet: {a, b}
+tape_alphabet: {a, b, □}
+
+δ(q0,a)=(q0,b,R)
+δ(q0,b)=(q0,b,R)
+δ(q0,□)=(q1,□,L)
+
+test: abab -> accept bbbb
+test: bbb -> accept bbb
+test: ε -> accept ε
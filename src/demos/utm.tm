@@ -0,0 +1,290 @@
+name: utm
+description: a universal turing machine, runs an encoded machine on an encoded input
+tracks: 1
+tape: two-way
+initial: fetch
+final: accept
+input_alphabet: {0, 1, :, #, >, L, R, @, |, ^}
+tape_alphabet: {0, 1, o, i, :, #, !, *, >, L, R, @, |, ^, □}
+bit = {0, 1}
+marked = {o, i}
+
+# The input is an encoded machine followed by its encoded tape, written
+# without the spaces:
+#
+#   <register> #<rule> #<rule> ... @ ^<cell> |<cell> ...
+#
+# States and symbols are numbered in binary with a fixed number of bits each;
+# the first bit of a state is 1 for final states and the blank is all zeros.
+# The register holds the current state and the symbol under the head as
+# '<state>:<symbol>'. A rule δ(p,a)=(q,b,D) is written '#p:a>q:bD' and the
+# cell under the head is the one after ^. The tests at the end run some of the
+# other demos encoded this way.
+
+# fetch: copy the bits of the cell after ^ into the symbol part of the register
+δ(fetch,?)=(fetch,?,L)
+δ(fetch,□)=(fetch_find,□,R)
+δ(fetch_find,?)=(fetch_find,?,R)
+δ(fetch_find,^)=(fetch_bit,^,R)
+δ(fetch_bit,[marked])=(fetch_bit,?,R)
+δ(fetch_bit,0)=(carry0,o,L)
+δ(fetch_bit,1)=(carry1,i,L)
+δ(fetch_bit,|)=(fetched,|,L)
+δ(fetch_bit,□)=(fetched,□,L)
+δ(carry0,?)=(carry0,?,L)
+δ(carry0,□)=(put0,□,R)
+δ(put0,[bit])=(put0,?,R)
+δ(put0,:)=(put0_bit,:,R)
+δ(put0_bit,[marked])=(put0_bit,?,R)
+δ(put0_bit,0)=(fetch,o,L)
+δ(put0_bit,1)=(fetch,o,L)
+δ(carry1,?)=(carry1,?,L)
+δ(carry1,□)=(put1,□,R)
+δ(put1,[bit])=(put1,?,R)
+δ(put1,:)=(put1_bit,:,R)
+δ(put1_bit,[marked])=(put1_bit,?,R)
+δ(put1_bit,0)=(fetch,i,L)
+δ(put1_bit,1)=(fetch,i,L)
+δ(fetched,o)=(fetched,0,L)
+δ(fetched,i)=(fetched,1,L)
+δ(fetched,^)=(unmark,^,L)
+δ(unmark,?)=(unmark,?,L)
+δ(unmark,□)=(unmark_bit,□,R)
+δ(unmark_bit,o)=(unmark_bit,0,R)
+δ(unmark_bit,i)=(unmark_bit,1,R)
+δ(unmark_bit,[bit])=(unmark_bit,?,R)
+δ(unmark_bit,:)=(unmark_bit,:,R)
+δ(unmark_bit,#)=(match,#,L)
+δ(unmark_bit,@)=(match,@,L)
+
+# match: compare the register with the key of each rule still marked #; a
+# rule that differs is marked ! and the first one that agrees is marked *
+δ(match,?)=(match,?,L)
+δ(match,□)=(match_bit,□,R)
+δ(match_bit,[marked])=(match_bit,?,R)
+δ(match_bit,:)=(match_bit,:,R)
+δ(match_bit,0)=(compare0,o,R)
+δ(match_bit,1)=(compare1,i,R)
+δ(match_bit,#)=(found,#,L)
+δ(match_bit,!)=(found,!,L)
+δ(compare0,?)=(compare0,?,R)
+δ(compare0,#)=(compare0_key,#,R)
+δ(compare0,@)=(halt,@,L)
+δ(compare0_key,[marked])=(compare0_key,?,R)
+δ(compare0_key,:)=(compare0_key,:,R)
+δ(compare0_key,0)=(match,o,L)
+δ(compare0_key,1)=(differ,1,L)
+δ(compare1,?)=(compare1,?,R)
+δ(compare1,#)=(compare1_key,#,R)
+δ(compare1,@)=(halt,@,L)
+δ(compare1_key,[marked])=(compare1_key,?,R)
+δ(compare1_key,:)=(compare1_key,:,R)
+δ(compare1_key,1)=(match,i,L)
+δ(compare1_key,0)=(differ,0,L)
+δ(differ,?)=(differ,?,L)
+δ(differ,#)=(differ_key,!,R)
+δ(differ_key,o)=(differ_key,0,R)
+δ(differ_key,i)=(differ_key,1,R)
+δ(differ_key,[bit])=(differ_key,?,R)
+δ(differ_key,:)=(differ_key,:,R)
+δ(differ_key,>)=(differ_register,>,L)
+δ(differ_register,?)=(differ_register,?,L)
+δ(differ_register,□)=(differ_bit,□,R)
+δ(differ_bit,o)=(differ_bit,0,R)
+δ(differ_bit,i)=(differ_bit,1,R)
+δ(differ_bit,[bit])=(differ_bit,?,R)
+δ(differ_bit,:)=(differ_bit,:,R)
+δ(differ_bit,#)=(match,#,L)
+δ(differ_bit,!)=(match,!,L)
+
+# no rule applies: accept if the first bit of the state is set
+δ(halt,?)=(halt,?,L)
+δ(halt,□)=(halt_bit,□,R)
+δ(halt_bit,1)=(accept,1,R)
+δ(halt_bit,i)=(accept,i,R)
+
+# execute: copy the value of the rule into the register...
+δ(found,?)=(found,?,R)
+δ(found,#)=(copy,*,R)
+δ(copy,?)=(copy,?,R)
+δ(copy,>)=(copy_bit,>,R)
+δ(copy_bit,[marked])=(copy_bit,?,R)
+δ(copy_bit,:)=(copy_bit,:,R)
+δ(copy_bit,0)=(write0,o,L)
+δ(copy_bit,1)=(write1,i,L)
+δ(copy_bit,L)=(storeL,L,L)
+δ(copy_bit,R)=(storeR,R,L)
+δ(write0,?)=(write0,?,L)
+δ(write0,□)=(write0_bit,□,R)
+δ(write0_bit,[bit])=(write0_bit,?,R)
+δ(write0_bit,:)=(write0_bit,:,R)
+δ(write0_bit,o)=(next_bit,0,R)
+δ(write0_bit,i)=(next_bit,0,R)
+δ(write1,?)=(write1,?,L)
+δ(write1,□)=(write1_bit,□,R)
+δ(write1_bit,[bit])=(write1_bit,?,R)
+δ(write1_bit,:)=(write1_bit,:,R)
+δ(write1_bit,o)=(next_bit,1,R)
+δ(write1_bit,i)=(next_bit,1,R)
+δ(next_bit,?)=(next_bit,?,R)
+δ(next_bit,*)=(copy,*,R)
+
+# ...write its symbol part to the cell after ^...
+δ(storeL,?)=(storeL,?,L)
+δ(storeL,□)=(storeL_state,□,R)
+δ(storeL_state,[bit])=(storeL_state,?,R)
+δ(storeL_state,:)=(storeL_bit,:,R)
+δ(storeL_bit,[marked])=(storeL_bit,?,R)
+δ(storeL_bit,0)=(storeL0,o,R)
+δ(storeL_bit,1)=(storeL1,i,R)
+δ(storeL_bit,#)=(cleanL,#,L)
+δ(storeL_bit,!)=(cleanL,!,L)
+δ(storeL_bit,*)=(cleanL,*,L)
+δ(storeL0,?)=(storeL0,?,R)
+δ(storeL0,^)=(storeL0_cell,^,R)
+δ(storeL0_cell,[marked])=(storeL0_cell,?,R)
+δ(storeL0_cell,0)=(storeL,o,L)
+δ(storeL0_cell,1)=(storeL,o,L)
+δ(storeL1,?)=(storeL1,?,R)
+δ(storeL1,^)=(storeL1_cell,^,R)
+δ(storeL1_cell,[marked])=(storeL1_cell,?,R)
+δ(storeL1_cell,0)=(storeL,i,L)
+δ(storeL1_cell,1)=(storeL,i,L)
+δ(storeR,?)=(storeR,?,L)
+δ(storeR,□)=(storeR_state,□,R)
+δ(storeR_state,[bit])=(storeR_state,?,R)
+δ(storeR_state,:)=(storeR_bit,:,R)
+δ(storeR_bit,[marked])=(storeR_bit,?,R)
+δ(storeR_bit,0)=(storeR0,o,R)
+δ(storeR_bit,1)=(storeR1,i,R)
+δ(storeR_bit,#)=(cleanR,#,L)
+δ(storeR_bit,!)=(cleanR,!,L)
+δ(storeR_bit,*)=(cleanR,*,L)
+δ(storeR0,?)=(storeR0,?,R)
+δ(storeR0,^)=(storeR0_cell,^,R)
+δ(storeR0_cell,[marked])=(storeR0_cell,?,R)
+δ(storeR0_cell,0)=(storeR,o,L)
+δ(storeR0_cell,1)=(storeR,o,L)
+δ(storeR1,?)=(storeR1,?,R)
+δ(storeR1,^)=(storeR1_cell,^,R)
+δ(storeR1_cell,[marked])=(storeR1_cell,?,R)
+δ(storeR1_cell,0)=(storeR,i,L)
+δ(storeR1_cell,1)=(storeR,i,L)
+
+# ...clear the marks and move ^ to the next or previous cell
+δ(cleanL,?)=(cleanL,?,L)
+δ(cleanL,□)=(sweepL,□,R)
+δ(sweepL,o)=(sweepL,0,R)
+δ(sweepL,i)=(sweepL,1,R)
+δ(sweepL,!)=(sweepL,#,R)
+δ(sweepL,*)=(sweepL,#,R)
+δ(sweepL,[bit])=(sweepL,?,R)
+δ(sweepL,:)=(sweepL,:,R)
+δ(sweepL,#)=(sweepL,#,R)
+δ(sweepL,>)=(sweepL,>,R)
+δ(sweepL,L)=(sweepL,L,R)
+δ(sweepL,R)=(sweepL,R,R)
+δ(sweepL,@)=(sweepL,@,R)
+δ(sweepL,|)=(sweepL,|,R)
+δ(sweepL,^)=(sweepL,^,R)
+δ(sweepL,□)=(moveL,□,L)
+δ(moveL,?)=(moveL,?,L)
+δ(cleanR,?)=(cleanR,?,L)
+δ(cleanR,□)=(sweepR,□,R)
+δ(sweepR,o)=(sweepR,0,R)
+δ(sweepR,i)=(sweepR,1,R)
+δ(sweepR,!)=(sweepR,#,R)
+δ(sweepR,*)=(sweepR,#,R)
+δ(sweepR,[bit])=(sweepR,?,R)
+δ(sweepR,:)=(sweepR,:,R)
+δ(sweepR,#)=(sweepR,#,R)
+δ(sweepR,>)=(sweepR,>,R)
+δ(sweepR,L)=(sweepR,L,R)
+δ(sweepR,R)=(sweepR,R,R)
+δ(sweepR,@)=(sweepR,@,R)
+δ(sweepR,|)=(sweepR,|,R)
+δ(sweepR,^)=(sweepR,^,R)
+δ(sweepR,□)=(moveR,□,L)
+δ(moveR,?)=(moveR,?,L)
+δ(moveR,^)=(right,|,R)
+δ(moveL,^)=(left,|,L)
+δ(right,[bit])=(right,?,R)
+δ(right,|)=(fetch,^,L)
+δ(right,□)=(append,^,L)
+δ(left,[bit])=(left,?,L)
+δ(left,|)=(fetch,^,L)
+δ(left,@)=(extend,@,R)
+
+# past the right end: add a blank cell, one 0 for each bit of the register's
+# symbol part
+δ(append,?)=(append,?,L)
+δ(append,□)=(append_state,□,R)
+δ(append_state,[bit])=(append_state,?,R)
+δ(append_state,:)=(append_bit,:,R)
+δ(append_bit,[marked])=(append_bit,?,R)
+δ(append_bit,0)=(append_zero,o,R)
+δ(append_bit,1)=(append_zero,i,R)
+δ(append_bit,#)=(counted,#,L)
+δ(append_bit,@)=(counted,@,L)
+δ(append_zero,?)=(append_zero,?,R)
+δ(append_zero,□)=(append,0,L)
+δ(counted,o)=(counted,0,L)
+δ(counted,i)=(counted,1,L)
+δ(counted,:)=(fetch,:,L)
+
+# past the left end: shift the tape right to make room for a blank cell
+δ(extend,|)=(shift|,^,R)
+δ(extend_count,?)=(extend_count,?,L)
+δ(extend_count,□)=(extend_state,□,R)
+δ(extend_state,[bit])=(extend_state,?,R)
+δ(extend_state,:)=(extend_bit,:,R)
+δ(extend_bit,[marked])=(extend_bit,?,R)
+δ(extend_bit,0)=(extend_zero,o,R)
+δ(extend_bit,1)=(extend_zero,i,R)
+δ(extend_bit,#)=(counted,#,L)
+δ(extend_bit,@)=(counted,@,L)
+δ(extend_zero,?)=(extend_zero,?,R)
+δ(extend_zero,@)=(extend_head,@,R)
+δ(extend_head,^)=(insert_zero,^,R)
+δ(insert_zero,0)=(shift0,0,R)
+δ(insert_zero,1)=(shift1,0,R)
+δ(insert_zero,|)=(shift|,0,R)
+δ(shift0,0)=(shift0,0,R)
+δ(shift0,1)=(shift1,0,R)
+δ(shift0,|)=(shift|,0,R)
+δ(shift0,^)=(shift^,0,R)
+δ(shift0,□)=(extend_count,0,L)
+δ(shift1,0)=(shift0,1,R)
+δ(shift1,1)=(shift1,1,R)
+δ(shift1,|)=(shift|,1,R)
+δ(shift1,^)=(shift^,1,R)
+δ(shift1,□)=(extend_count,1,L)
+δ(shift|,0)=(shift0,|,R)
+δ(shift|,1)=(shift1,|,R)
+δ(shift|,|)=(shift|,|,R)
+δ(shift|,^)=(shift^,|,R)
+δ(shift|,□)=(extend_count,|,L)
+δ(shift^,0)=(shift0,^,R)
+δ(shift^,1)=(shift1,^,R)
+δ(shift^,|)=(shift|,^,R)
+δ(shift^,^)=(shift^,^,R)
+δ(shift^,□)=(extend_count,^,L)
+
+# increment on 1011
+test: 000:00#000:01>000:01R#000:10>000:10R#000:00>001:00L#001:10>001:01L#001:01>110:10L#001:00>110:10L@^10|01|10|10 -> accept
+# increment on 111
+test: 000:00#000:01>000:01R#000:10>000:10R#000:00>001:00L#001:10>001:01L#001:01>110:10L#001:00>110:10L@^10|10|10 -> accept
+# decrement on 100
+test: 000:00#000:01>000:01R#000:10>000:10R#000:00>001:00L#001:01>001:10L#001:10>110:01L@^10|01|01 -> accept
+# decrement on 000
+test: 000:00#000:01>000:01R#000:10>000:10R#000:00>001:00L#001:01>001:10L#001:10>110:01L@^01|01|01 -> reject
+# palindrome on aba
+test: 0000:00#0000:01>0001:00R#0000:10>0010:00R#0000:00>1011:00R#0001:01>0001:01R#0001:10>0001:10R#0001:00>0100:00L#0010:01>0010:01R#0010:10>0010:10R#0010:00>0101:00L#0100:01>0110:00L#0100:00>1011:00R#0101:10>0110:00L#0101:00>1011:00R#0110:01>0110:01L#0110:10>0110:10L#0110:00>0000:00R@^01|10|01 -> accept
+# palindrome on ab
+test: 0000:00#0000:01>0001:00R#0000:10>0010:00R#0000:00>1011:00R#0001:01>0001:01R#0001:10>0001:10R#0001:00>0100:00L#0010:01>0010:01R#0010:10>0010:10R#0010:00>0101:00L#0100:01>0110:00L#0100:00>1011:00R#0101:10>0110:00L#0101:00>1011:00R#0110:01>0110:01L#0110:10>0110:10L#0110:00>0000:00R@^01|10 -> reject
+# anbn on aabb
+test: 0000:000#0000:001>0001:011R#0001:001>0001:001R#0001:100>0001:100R#0001:010>0010:100L#0010:100>0010:100L#0010:001>0010:001L#0010:011>0000:011R#0000:100>0011:100R#0011:100>0011:100R#0011:000>1100:000L@^001|001|010|010 -> accept
+# anbn on aab
+test: 0000:000#0000:001>0001:011R#0001:001>0001:001R#0001:100>0001:100R#0001:010>0010:100L#0010:100>0010:100L#0010:001>0010:001L#0010:011>0000:011R#0000:100>0011:100R#0011:100>0011:100R#0011:000>1100:000L@^001|001|010 -> reject
+# beaver2 on ε
+test: 000:0#000:0>001:1R#000:1>001:1L#001:0>000:1L#001:1>110:1R@^0 -> accept
@@ -0,0 +1,57 @@
+name: ww
+description: accepts strings of 'a' & 'b' made of the same half twice (e.g. input: abab)
+tracks: 1
+tape: two-way
+initial: q0
+final: q10
+input_alphabet: {a, b}
+tape_alphabet: {a, b, A, B, X, Y, x, □}
+
+# find the middle by marking symbols from both ends, A/B on the left and X/Y
+# on the right
+δ(q0,a)=(q1,A,R)
+δ(q0,b)=(q1,B,R)
+δ(q0,□)=(q10,□,R)
+δ(q1,a)=(q1,a,R)
+δ(q1,b)=(q1,b,R)
+δ(q1,X)=(q2,X,L)
+δ(q1,Y)=(q2,Y,L)
+δ(q1,□)=(q2,□,L)
+δ(q2,a)=(q5,X,L)
+δ(q2,b)=(q5,Y,L)
+δ(q5,a)=(q5,a,L)
+δ(q5,b)=(q5,b,L)
+δ(q5,A)=(q0,A,R)
+δ(q5,B)=(q0,B,R)
+# everything is marked and the head is on the second half
+δ(q0,X)=(q9,X,L)
+δ(q0,Y)=(q9,Y,L)
+# cross off the first symbol of each half and compare them
+δ(q7,x)=(q7,x,R)
+δ(q7,A)=(q8,x,R)
+δ(q7,B)=(q11,x,R)
+δ(q7,□)=(q10,□,R)
+δ(q8,A)=(q8,A,R)
+δ(q8,B)=(q8,B,R)
+δ(q8,x)=(q8,x,R)
+δ(q8,X)=(q9,x,L)
+δ(q11,A)=(q11,A,R)
+δ(q11,B)=(q11,B,R)
+δ(q11,x)=(q11,x,R)
+δ(q11,Y)=(q9,x,L)
+# back to the start
+δ(q9,A)=(q9,A,L)
+δ(q9,B)=(q9,B,L)
+δ(q9,X)=(q9,X,L)
+δ(q9,Y)=(q9,Y,L)
+δ(q9,x)=(q9,x,L)
+δ(q9,□)=(q7,□,R)
+
+test: abab -> accept xxxx
+test: aa -> accept
+test: abaaba -> accept
+test: ε -> accept
+test: a -> reject
+test: ab -> reject
+test: abba -> reject
+test: aab -> reject
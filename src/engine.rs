@@ -0,0 +1,225 @@
+use crate::rules::RuleIndex;
+use crate::tape::{Symbol, Symbols, Tape};
+use crate::Machine;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Running,
+    /// Halted in a final state.
+    Accepted,
+    /// Halted in a state that isn't final.
+    Rejected,
+    /// Moved off the left end of a one-way-crash tape.
+    Crashed,
+}
+
+/// What a single step did.
+pub struct Step {
+    /// Index into `machine.transitions` of the rule that fired.
+    pub rule: usize,
+    pub read: String,
+    pub written: String,
+    /// Head position before the move.
+    pub position: i64,
+    /// The head stayed put at the left end of a one-way-stay tape.
+    pub stayed: bool,
+}
+
+/// What it takes to undo one step.
+struct Undo {
+    head: i64,
+    symbol: Symbol,
+    state: String,
+}
+
+/// One execution of a machine on an input, advanced a step at a time. A run
+/// made with `Run::with_history` records every step so that it can also be
+/// stepped backwards.
+pub struct Run<'m> {
+    pub machine: &'m Machine,
+    index: RuleIndex,
+    symbols: Symbols,
+    pub tape: Tape,
+    pub head: i64,
+    pub state: String,
+    pub steps: usize,
+    pub status: Status,
+    /// `None` unless the run records its steps.
+    history: Option<Vec<Undo>>,
+}
+
+impl<'m> Run<'m> {
+    /// `input` holds one character per track for every cell, e.g. "abba" is
+    /// the cells "ab" and "ba" on a two-track machine.
+    pub fn new(machine: &'m Machine, input: &str) -> Run<'m> {
+        let mut symbols = Symbols::new(machine.tracks);
+        let cells: Vec<Symbol> = input
+            .chars()
+            .collect::<Vec<char>>()
+            .chunks(machine.tracks)
+            .map(|chunk| symbols.intern(&chunk.iter().collect::<String>()))
+            .collect();
+        let mut run = Run {
+            machine,
+            index: RuleIndex::new(machine),
+            symbols,
+            tape: Tape::new(&cells, machine.tape_mode),
+            head: 0,
+            state: machine.states.initial_state.clone(),
+            steps: 0,
+            status: Status::Running,
+            history: None,
+        };
+        run.settle();
+        run
+    }
+
+    /// Like `Run::new`, but recording every step so that `back` can undo it.
+    pub fn with_history(machine: &'m Machine, input: &str) -> Run<'m> {
+        let mut run = Run::new(machine, input);
+        run.history = Some(Vec::new());
+        run
+    }
+
+    /// The cell at a tape position.
+    pub fn cell(&self, position: i64) -> &str {
+        self.symbols.name(self.tape.read(position))
+    }
+
+    /// The cell under the head.
+    pub fn current(&self) -> &str {
+        self.cell(self.head)
+    }
+
+    /// The rule that fires on the next step, if any.
+    pub fn next_rule(&self) -> Option<usize> {
+        if self.status != Status::Running {
+            return None;
+        }
+        self.index
+            .fire(&self.state, self.current())
+            .map(|(rule, _)| rule)
+    }
+
+    /// Fires one rule. Returns `None` once the machine has halted.
+    pub fn step(&mut self) -> Option<Step> {
+        if self.status != Status::Running {
+            return None;
+        }
+        let read = self.current().to_string();
+        let (rule, written) = self.index.fire(&self.state, &read)?;
+        let function = &self.machine.transitions[rule];
+        let position = self.head;
+        if let Some(history) = &mut self.history {
+            history.push(Undo {
+                head: self.head,
+                symbol: self.tape.read(self.head),
+                state: self.state.clone(),
+            });
+        }
+        let symbol = self.symbols.intern(&written);
+        self.tape.write(self.head, symbol);
+        self.state = function.rhs.state.clone();
+        let mut stayed = false;
+        if function.rhs.direction == 'L' {
+            match self.machine.tape_mode.move_left(self.head) {
+                Some(head) => {
+                    stayed = head == self.head;
+                    self.head = head;
+                }
+                None => self.status = Status::Crashed,
+            }
+        } else if function.rhs.direction == 'R' {
+            self.head += 1;
+        }
+        self.tape.reach(self.head);
+        self.steps += 1;
+        self.settle();
+        Some(Step {
+            rule,
+            read,
+            written,
+            position,
+            stayed,
+        })
+    }
+
+    /// Runs until the machine halts or `limit` steps have been taken, and
+    /// returns the status; `Running` means the limit was reached.
+    pub fn run(&mut self, limit: usize) -> Status {
+        while self.steps < limit && self.step().is_some() {}
+        self.status
+    }
+
+    /// The written part of the tape, one string per track, from the first
+    /// to the last cell that isn't blank on every track.
+    pub fn output(&self) -> Vec<String> {
+        let used: Vec<i64> = self
+            .tape
+            .span()
+            .filter(|&position| self.tape.read(position) != Symbols::BLANK)
+            .collect();
+        let mut tracks = vec![String::new(); self.machine.tracks];
+        if let (Some(&first), Some(&last)) = (used.first(), used.last()) {
+            for position in first..=last {
+                for (track, c) in tracks.iter_mut().zip(self.cell(position).chars()) {
+                    track.push(c);
+                }
+            }
+        }
+        tracks
+    }
+
+    /// The configuration in the usual notation, with the state written just
+    /// before the cell under the head, e.g. 'ab q1 ba'.
+    pub fn configuration(&self) -> String {
+        let written: Vec<i64> = self
+            .tape
+            .span()
+            .filter(|&position| self.tape.read(position) != Symbols::BLANK)
+            .collect();
+        let first = written.first().map_or(self.head, |&p| p.min(self.head));
+        let last = written.last().map_or(self.head, |&p| p.max(self.head));
+        let left: String = (first..self.head).map(|p| self.cell(p)).collect();
+        let right: String = (self.head..=last).map(|p| self.cell(p)).collect();
+        format!("{} {} {}", left, self.state, right)
+            .trim()
+            .to_string()
+    }
+
+    /// Undoes the last step. Returns false at step 0, or if the run doesn't
+    /// record its steps.
+    pub fn back(&mut self) -> bool {
+        let Some(undo) = self.history.as_mut().and_then(|history| history.pop()) else {
+            return false;
+        };
+        self.tape.write(undo.head, undo.symbol);
+        self.head = undo.head;
+        self.state = undo.state;
+        self.steps -= 1;
+        // only a running machine takes steps
+        self.status = Status::Running;
+        true
+    }
+
+    /// Moves backwards or forwards to just after step `target`, or as far as
+    /// the machine gets if it halts first.
+    pub fn seek(&mut self, target: usize) {
+        while self.steps > target && self.back() {}
+        while self.steps < target && self.step().is_some() {}
+    }
+
+    /// Halts the run if no rule applies any more.
+    fn settle(&mut self) {
+        if self.status != Status::Running {
+            return;
+        }
+        if self.index.fire(&self.state, self.current()).is_none() {
+            self.status = if self.machine.states.final_states.contains(&self.state) {
+                Status::Accepted
+            } else {
+                Status::Rejected
+            };
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::engine::Run;
+use crate::oracle::{self, Verdict};
+use crate::Machine;
+
+// Bounded equivalence: two machines agree if, on every input up to a length,
+// they reach the same verdict within the step limit and leave the same tape
+// behind. Agreeing up to a bound is evidence, not a proof.
+
+/// What one machine did with one input.
+#[derive(PartialEq)]
+pub struct Behaviour {
+    pub verdict: Verdict,
+    pub output: Vec<String>,
+}
+
+pub fn behaviour(machine: &Machine, input: &str, limit: usize) -> Behaviour {
+    let mut run = Run::new(machine, input);
+    let verdict = Verdict::of(run.run(limit));
+    Behaviour {
+        verdict,
+        output: run.output(),
+    }
+}
+
+/// The configurations a machine goes through, one per step.
+pub fn trace(machine: &Machine, input: &str, limit: usize) -> Vec<String> {
+    let mut run = Run::new(machine, input);
+    let mut configurations = vec![run.configuration()];
+    while run.steps < limit && run.step().is_some() {
+        configurations.push(run.configuration());
+    }
+    configurations
+}
+
+pub struct Report {
+    pub checked: usize,
+    /// The first input the machines disagree on.
+    pub difference: Option<String>,
+}
+
+pub fn compare(
+    a: &Machine,
+    b: &Machine,
+    alphabet: &[char],
+    max_length: usize,
+    limit: usize,
+) -> Report {
+    let mut checked = 0;
+    for input in oracle::inputs(alphabet, max_length) {
+        checked += 1;
+        if behaviour(a, &input, limit) != behaviour(b, &input, limit) {
+            return Report {
+                checked,
+                difference: Some(input),
+            };
+        }
+    }
+    Report {
+        checked,
+        difference: None,
+    }
+}
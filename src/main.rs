@@ -1,114 +1,150 @@
+use check::Severity;
 use colored::*;
 use csv::Reader;
 use csv::StringRecord;
+use engine::{Run, Status};
 use prettytable::{format, Cell, Row, Table};
 use regex::Regex;
+use rules::SymbolSet;
 use std::env;
 use std::fs::File;
 use std::io::{self, Write};
-
-#[derive(PartialEq)]
+use tape::TapeMode;
+
+mod check;
+mod completeness;
+mod compose;
+mod debugger;
+mod definition;
+mod demos;
+mod engine;
+mod equivalence;
+mod matrix;
+mod minimize;
+mod multitape;
+mod multitrack;
+mod normalize;
+mod ntm;
+mod oneway;
+mod oracle;
+mod repl;
+mod rules;
+mod suite;
+mod tape;
+mod utm;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, PartialEq)]
 struct LHS {
     state: String,
     input: String,
 }
 
-#[derive(PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, PartialEq)]
 struct RHS {
     state: String,
     replacement: String,
     direction: char,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 struct TransitionFunction {
     lhs: LHS,
     rhs: RHS,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 struct KeyStates {
     initial_state: String,
     final_states: Vec<String>,
 }
 
+#[derive(Clone)]
 struct Machine {
     transitions: Vec<TransitionFunction>,
     states: KeyStates,
     tracks: usize,
+    tape_mode: TapeMode,
+    sets: Vec<SymbolSet>,
+    // Σ and Γ of the formal definition; None when the machine doesn't declare them
+    input_alphabet: Option<Vec<char>>,
+    tape_alphabet: Option<Vec<char>>,
+}
+
+impl Machine {
+    /// A one-track machine without states or rules, to be built up bit by bit.
+    fn empty() -> Machine {
+        Machine {
+            transitions: Vec::new(),
+            states: KeyStates {
+                initial_state: String::new(),
+                final_states: Vec::new(),
+            },
+            tracks: 1,
+            tape_mode: TapeMode::TwoWay,
+            sets: Vec::new(),
+            input_alphabet: None,
+            tape_alphabet: None,
+        }
+    }
+}
+
+// command-line flags that apply to every option
+struct Options {
+    tape_mode: Option<TapeMode>,
+    debug: bool,
 }
 
 // main
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let debug = take_flag(&mut args, "-debug");
+    let tape_mode = match take_tape_mode(&mut args) {
+        Ok(mode) => mode,
+        Err(e) => {
+            println!("{}", format!("Error: {}", e).red());
+            return;
+        }
+    };
+    let options = Options { tape_mode, debug };
     let arg_len = args.len();
     match arg_len {
-        1 => default_behaviour(),
-        3 => option_behaviour(args),
-        2 | _ => help_behaviour(args),
+        1 => default_behaviour(&options),
+        2 if args[1] == "-repl" => {
+            let mut turing_machine = Machine::empty();
+            if let Some(mode) = options.tape_mode {
+                turing_machine.tape_mode = mode;
+            }
+            repl::repl(turing_machine, &options)
+        }
+        2 => help_behaviour(args),
+        _ => option_behaviour(args, &options),
     }
 }
 
 // main functions
-fn default_behaviour() {
+fn default_behaviour(options: &Options) {
     println!("Turing Machine Simulator");
     print!("Number of tracks: ");
     io::stdout().flush().expect("failed to flush");
     let tracks: usize = get_input().trim().parse().unwrap();
-    let transitions = get_transitions(tracks);
+    let (transitions, sets) = get_transitions(tracks);
     let states = get_states(&transitions);
+    let (input_alphabet, tape_alphabet) = get_alphabets();
     let turing_machine = Machine {
-        transitions: transitions,
-        states: states,
-        tracks: tracks,
-    };
-    loop {
-        print!("Track 1: ");
-        io::stdout().flush().expect("failed to flush");
-        let mut inputs: Vec<String> = vec![get_input().trim().to_string().replace("\r\n", "")];
-
-        // Get the remaining inputs, checking if they have the same length as the first one
-        for i in 1..tracks {
-            loop {
-                print!("Tape (Track {}): ", i + 1);
-                io::stdout().flush().expect("failed to flush");
-                let input = get_input().trim().to_string().replace("\r\n", "");
-                if input.len() == inputs[0].len() {
-                    inputs.push(input);
-                    break;
-                } else {
-                    println!("Error: Tracks must have the same length");
-                }
-            }
-        }
-
-        // Combine the inputs
-        let combined: String = (0..inputs[0].len())
-            .map(|i| {
-                inputs
-                    .iter()
-                    .map(|s| s.chars().nth(i).unwrap())
-                    .collect::<String>()
-            })
-            .collect();
-        parse(
-            combined,
-            &turing_machine.transitions,
-            &turing_machine.states,
-            turing_machine.tracks,
-        );
-        println!("Parse another string? (Y/N)");
-        if get_input()
-            .trim()
-            .to_string()
-            .replace("\r\n", "")
-            .to_uppercase()
-            == "Y"
-        {
-            continue;
-        } else {
-            break;
-        }
+        transitions,
+        states,
+        tracks,
+        tape_mode: options.tape_mode.unwrap_or(TapeMode::TwoWay),
+        sets,
+        input_alphabet,
+        tape_alphabet,
+    };
+    let saved = offer_save(&turing_machine);
+    run_inputs(&turing_machine, options);
+    if !saved {
+        offer_save(&turing_machine);
     }
 }
 
@@ -121,19 +157,134 @@ fn help_behaviour(args: Vec<String>) {
     println!("\nOptions:");
     println!("-help : Shows help menu");
     println!("-csv <path> : loads transition functions from csv file");
-    println!("-demo 0 : translates every 'a' to 'b'");
-    println!("-demo 1 : accepts strings in form of a(n)b(n)");
-    println!("-demo 2 : copies strings of '1'");
-    println!("-demo 3 : checks two tracks of 'a' & 'b' and finds where tracks match");
-    println!("-demo 4 : a turing machine for multiplication (e.g. input: 11*11)");
+    println!("-file <path> : loads a machine definition (.tm) file");
+    println!("-check <path> : reports problems in a machine definition without running it");
+    println!("-complete <path> [reject_state] : lists the (state, symbol) pairs without a rule and optionally fills them in with rules to a new reject state");
+    println!(
+        "-compose <path> : builds one machine out of the machines listed in a composition file"
+    );
+    println!(
+        "-test <path> [suite] : runs the test cases of a machine and exits with 1 if any fails"
+    );
+    println!("-oracle <path> <reference> [length] : compares a machine with a reference on every input up to a length (8 by default)");
+    println!("    reference: regex:<pattern>, a machine file or one of");
+    for (name, description, _) in oracle::PREDICATES {
+        println!("    {} : {}", name, description);
+    }
+    println!("-equivalent <path> <path> [length] [steps] : finds the first input up to a length (8 by default) on which two machines differ, running each for at most steps");
+    println!("-single-track <path> [reachable] [path] : converts a multi-track machine to a single-track one over tuple symbols, optionally only the tuples it can write, and saves it");
+    println!("-single-tape <path> [length] [path] : converts a k-tape machine to a single-tape one, reports the step overhead on inputs up to a length (6 by default) and saves it");
+    println!("-one-way <path> [path] : converts a machine for the two-way tape to one that runs on a one-way tape, verifies it and saves it");
+    println!("-determinize <path> [length] [path] : builds the deterministic 3-tape machine for a nondeterministic one, compares them on inputs up to a length (4 by default) and saves it, as a single-tape machine unless the path ends in .mtm");
+    println!("-minimize <path> [path] : removes unreachable states and rules that never fire, merges states that behave alike, renames the states q0, q1, ..., verifies the result and prints or saves it");
+    println!("-normalize <path> [table] [path] : renames the states q0, q1, ... breadth-first from the initial state, sorts the rules by state and symbol and prints them, optionally as a state × symbol table, or saves them");
+    println!("-matrix <path> [path] : shows the rules as a state × symbol matrix, highlighting conflicts, missing entries and final states, and exports it to Markdown (.md), CSV (.csv) or HTML (.html)");
+    println!("-utm encode <path> [input] : writes a single-track machine and an input in the encoding the utm demo runs on");
+    println!("-utm decode <encoding> [symbols] : reads a machine and its input back from that encoding, with symbols 1, 2, ... named by the given symbols in order");
+    println!("-utm <path> [length] : runs a machine natively and on the utm demo for every input up to a length (3 by default) and compares them");
+    println!("-repl [path] : builds and edits a machine command by command, optionally starting from a file");
+    println!("-debug : steps through runs in the interactive debugger instead of printing the whole trace");
+    println!("-tape <mode> : tape model, one of two-way (default), one-way-stay, one-way-crash");
+    println!("-demo list : lists the demos");
+    println!("-demo show <name> : prints the definition of a demo");
+    println!("-demo test [name] : runs the test cases of one demo, or of all of them");
+    println!("-demo export <name> [path] : writes a demo to a definition file");
+    for (index, demo) in demos::demos().iter().enumerate() {
+        println!("-demo {} | {} : {}", index, demo.name, demo.description);
+    }
     println!("\n*Run without options to input your own turing machine")
 }
 
-fn option_behaviour(args: Vec<String>) {
+fn option_behaviour(args: Vec<String>, options: &Options) {
     if args[1] == "-demo" {
-        demo_behaviour(args);
+        demo_behaviour(args, options);
     } else if args[1] == "-csv" {
-        if let Err(e) = csv_behaviour(args) {
+        if let Err(e) = csv_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+        }
+    } else if args[1] == "-compose" {
+        match compose::read_composition(&args[2]) {
+            Ok(mut turing_machine) => {
+                if let Some(mode) = options.tape_mode {
+                    turing_machine.tape_mode = mode;
+                }
+                println!("Turing Machine Simulator");
+                println!("{}", definition::write_machine(&turing_machine));
+                run_inputs(&turing_machine, options);
+            }
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    } else if args[1] == "-check" {
+        if let Err(e) = check_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+        }
+    } else if args[1] == "-complete" {
+        if let Err(e) = complete_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-test" {
+        if let Err(e) = test_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-oracle" && args.len() > 3 {
+        if let Err(e) = oracle_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-equivalent" && args.len() > 3 {
+        if let Err(e) = equivalent_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-single-track" {
+        if let Err(e) = single_track_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-single-tape" {
+        if let Err(e) = single_tape_behaviour(args) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-one-way" {
+        if let Err(e) = one_way_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-determinize" {
+        if let Err(e) = determinize_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-minimize" {
+        if let Err(e) = minimize_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-normalize" {
+        if let Err(e) = normalize_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-matrix" {
+        if let Err(e) = matrix_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-utm" {
+        if let Err(e) = utm_behaviour(args, options) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args[1] == "-repl" {
+        match load_machine(&args[2], options) {
+            Ok(turing_machine) => repl::repl(turing_machine, options),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    } else if args[1] == "-file" {
+        if let Err(e) = file_behaviour(args, options) {
             eprintln!("Error: {}", e);
         }
     } else {
@@ -141,69 +292,847 @@ fn option_behaviour(args: Vec<String>) {
     }
 }
 
-fn demo_behaviour(args: Vec<String>) {
-    let demo_index: usize = match args[2].parse() {
-        Ok(idx) => idx,
-        Err(_) => {
-            println!("Invalid demo index");
-            return;
+fn demo_behaviour(args: Vec<String>, options: &Options) {
+    match args[2].as_str() {
+        "list" => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_BOX_CHARS);
+            table.set_titles(Row::new(vec![
+                Cell::new("#"),
+                Cell::new("Name"),
+                Cell::new("Description"),
+            ]));
+            for (index, demo) in demos::demos().iter().enumerate() {
+                table.add_row(Row::new(vec![
+                    Cell::new(&index.to_string()),
+                    Cell::new(demo.name),
+                    Cell::new(demo.description),
+                ]));
+            }
+            table.printstd();
         }
+        "test" => test_demos(args.get(3)),
+        "show" | "export" if args.len() < 4 => {
+            println!(
+                "{}",
+                format!("Error: -demo {} needs a demo name", args[2]).red()
+            )
+        }
+        "show" => match demos::find(&args[3]) {
+            Some(demo) => print!("{}", demo.source),
+            None => println!("Unknown demo '{}', see -demo list", args[3]),
+        },
+        "export" => match demos::find(&args[3]) {
+            Some(demo) => {
+                let path = args.get(4).cloned().unwrap_or(format!("{}.tm", demo.name));
+                match std::fs::write(&path, demo.source) {
+                    Ok(()) => println!("Saved to {}", path),
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+            }
+            None => println!("Unknown demo '{}', see -demo list", args[3]),
+        },
+        key => match demos::find(key) {
+            Some(demo) => {
+                let mut turing_machine = demo.machine();
+                if let Some(mode) = options.tape_mode {
+                    turing_machine.tape_mode = mode;
+                }
+                run_inputs(&turing_machine, options);
+            }
+            None => println!("Unknown demo '{}', see -demo list", key),
+        },
+    }
+}
+
+/// Runs the test cases of one demo, or of all of them.
+fn test_demos(name: Option<&String>) {
+    let selected = match name {
+        Some(name) => match demos::find(name) {
+            Some(demo) => vec![demo],
+            None => {
+                println!("Unknown demo '{}', see -demo list", name);
+                return;
+            }
+        },
+        None => demos::demos(),
     };
-    let dem = demos();
-    if demo_index < dem.len() {
-        let demo = &dem[demo_index];
-        loop {
-            print!("Track 1: ");
-            io::stdout().flush().expect("failed to flush");
-            let mut inputs: Vec<String> = vec![get_input().trim().to_string().replace("\r\n", "")];
-            // Get the remaining inputs, checking if they have the same length as the first one
-            for i in 1..demo.tracks {
-                loop {
-                    print!("Track {}: ", i + 1);
-                    io::stdout().flush().expect("failed to flush");
-                    let input = get_input().trim().to_string().replace("\r\n", "");
-                    if input.len() == inputs[0].len() {
-                        inputs.push(input);
-                        break;
-                    } else {
-                        println!("Error: Tracks must have the same length");
-                    }
+    let (mut passed, mut failed) = (0, 0);
+    for demo in selected {
+        let (p, f) = run_suite(demo.name, &demo.machine(), &demo.suite());
+        passed += p;
+        failed += f;
+    }
+    println!("{} passed, {} failed", passed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn test_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let path = &args[2];
+    let turing_machine = load_machine(path, options)?;
+    let invalid = io::Error::other;
+    let mut cases = suite::Suite {
+        cases: Vec::new(),
+        limit: suite::STEP_LIMIT,
+    };
+    if !definition::is_csv(path) {
+        let text = std::fs::read_to_string(path)?;
+        cases = suite::definition_suite(&text, turing_machine.tracks)
+            .map_err(|e| invalid(format!("{}: {}", path, e)))?;
+    }
+    // an explicit suite file, or the one next to the machine if there is one
+    let suite_path = match args.get(3) {
+        Some(suite_path) => Some(suite_path.clone()),
+        None => {
+            let sibling = std::path::Path::new(path).with_extension("tests");
+            sibling
+                .exists()
+                .then(|| sibling.to_string_lossy().to_string())
+        }
+    };
+    if let Some(suite_path) = suite_path {
+        let text = std::fs::read_to_string(&suite_path)?;
+        let file = suite::parse_suite(&text, turing_machine.tracks)
+            .map_err(|e| invalid(format!("{}: {}", suite_path, e)))?;
+        cases.cases.extend(file.cases);
+        cases.limit = file.limit;
+    }
+    if cases.cases.is_empty() {
+        return Err(invalid(format!("no test cases found for {}", path)));
+    }
+    let (passed, failed) = run_suite(path, &turing_machine, &cases);
+    println!("{} passed, {} failed", passed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Runs every case of a suite, printing a line per case and what went wrong
+/// for the ones that fail. Returns how many passed and failed.
+fn run_suite(label: &str, turing_machine: &Machine, cases: &suite::Suite) -> (usize, usize) {
+    let (mut passed, mut failed) = (0, 0);
+    for (text, case) in &cases.cases {
+        match suite::run_case(turing_machine, case, cases.limit) {
+            Ok(()) => {
+                passed += 1;
+                println!("{} {}: {}", "PASS".green(), label, text);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {}", "FAIL".red(), label, text);
+                for line in e.lines() {
+                    println!("    {}", line);
                 }
             }
-            // Combine the inputs
-            let combined: String = (0..inputs[0].len())
-                .map(|i| {
-                    inputs
-                        .iter()
-                        .map(|s| s.chars().nth(i).unwrap())
-                        .collect::<String>()
-                })
-                .collect();
-            parse(combined, &demo.transitions, &demo.states, demo.tracks);
-            println!("Parse another string? (Y/N)");
-            if get_input()
-                .trim()
-                .to_string()
-                .replace("\r\n", "")
-                .to_uppercase()
-                == "Y"
-            {
-                continue;
+        }
+    }
+    (passed, failed)
+}
+
+fn csv_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    println!("Turing Machine Simulator");
+    let turing_machine = load_machine(&args[2], options)?;
+    run_inputs(&turing_machine, options);
+    Ok(())
+}
+
+fn file_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    println!("Turing Machine Simulator");
+    let turing_machine = load_machine(&args[2], options)?;
+    run_inputs(&turing_machine, options);
+    Ok(())
+}
+
+fn check_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let diagnostics = check::check(&turing_machine);
+    check::print(&diagnostics);
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    println!(
+        "{} error(s), {} warning(s), {} note(s)",
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info)
+    );
+    if count(Severity::Error) > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn complete_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let mut turing_machine = load_machine(&args[2], options)?;
+    let missing = completeness::missing(&turing_machine);
+    if missing.is_empty() {
+        println!(
+            "{}",
+            "Every non-final state has a rule for every symbol".green()
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("State"),
+        Cell::new("Symbols without a rule"),
+    ]));
+    let mut states: Vec<&String> = Vec::new();
+    for (state, _) in &missing {
+        if !states.contains(&state) {
+            states.push(state);
+        }
+    }
+    for state in states {
+        let symbols: Vec<&str> = missing
+            .iter()
+            .filter(|(s, _)| s == state)
+            .map(|(_, cell)| cell.as_str())
+            .collect();
+        table.add_row(Row::new(vec![
+            Cell::new(state),
+            Cell::new(&symbols.join(" ")),
+        ]));
+    }
+    table.printstd();
+    println!("{} missing (state, symbol) pair(s)", missing.len());
+
+    if let Some(reject) = args.get(3) {
+        let added = completeness::fill(&mut turing_machine, reject).map_err(io::Error::other)?;
+        println!("\nAdded {} rule(s) to '{}':\n", added, reject);
+        println!("{}", definition::write_machine(&turing_machine));
+    }
+    Ok(())
+}
+
+/// Cases shown when a check finds more counterexamples.
+const COUNTEREXAMPLES_SHOWN: usize = 10;
+
+fn oracle_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    if turing_machine.tracks != 1 {
+        return Err(io::Error::other(
+            "only single-track machines can be checked",
+        ));
+    }
+    let reference = oracle::Reference::parse(&args[3], |path| {
+        load_machine(path, options).map_err(|e| e.to_string())
+    })
+    .map_err(io::Error::other)?;
+    let max_length: usize = match args.get(4) {
+        Some(n) => n
+            .parse()
+            .map_err(|_| io::Error::other(format!("'{}' is not a length", n)))?,
+        None => 8,
+    };
+    let alphabet = oracle::input_alphabet(&turing_machine);
+    let report = oracle::compare(&turing_machine, &reference, &alphabet, max_length);
+    println!(
+        "Checked {} input(s) over {} of length 0 to {}",
+        report.checked,
+        rules::format_symbols(&alphabet),
+        max_length
+    );
+    if report.counterexamples.is_empty() {
+        println!("{}", "No counterexamples".green());
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("Input"),
+        Cell::new("Machine"),
+        Cell::new("Reference"),
+    ]));
+    for counterexample in report.counterexamples.iter().take(COUNTEREXAMPLES_SHOWN) {
+        let input = if counterexample.input.is_empty() {
+            "ε"
+        } else {
+            &counterexample.input
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(input),
+            Cell::new(counterexample.machine.name()),
+            Cell::new(counterexample.reference.name()),
+        ]));
+    }
+    table.printstd();
+    println!(
+        "{}",
+        format!("{} counterexample(s)", report.counterexamples.len()).red()
+    );
+    std::process::exit(1);
+}
+
+/// Steps of each trace shown when two machines differ.
+const TRACE_SHOWN: usize = 50;
+
+fn equivalent_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let a = load_machine(&args[2], options)?;
+    let b = load_machine(&args[3], options)?;
+    if a.tracks != 1 || b.tracks != 1 {
+        return Err(io::Error::other(
+            "only single-track machines can be compared",
+        ));
+    }
+    let alphabet = oracle::input_alphabet(&a);
+    let mut other = oracle::input_alphabet(&b);
+    let mut sorted = alphabet.clone();
+    sorted.sort();
+    other.sort();
+    if sorted != other {
+        return Err(io::Error::other(format!(
+            "the machines have different input alphabets, {} and {}",
+            rules::format_symbols(&alphabet),
+            rules::format_symbols(&other)
+        )));
+    }
+    let number = |index: usize, default: usize| -> io::Result<usize> {
+        match args.get(index) {
+            Some(n) => n
+                .parse()
+                .map_err(|_| io::Error::other(format!("'{}' is not a number", n))),
+            None => Ok(default),
+        }
+    };
+    let max_length = number(4, 8)?;
+    let limit = number(5, oracle::STEP_LIMIT)?;
+
+    let report = equivalence::compare(&a, &b, &alphabet, max_length, limit);
+    let Some(input) = report.difference else {
+        println!(
+            "{}",
+            format!(
+                "The machines agree on all {} input(s) over {} of length 0 to {}",
+                report.checked,
+                rules::format_symbols(&alphabet),
+                max_length
+            )
+            .green()
+        );
+        return Ok(());
+    };
+
+    let shown = if input.is_empty() { "ε" } else { &input };
+    println!("{}", format!("The machines differ on '{}'", shown).red());
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("Step"),
+        Cell::new(&args[2]),
+        Cell::new(&args[3]),
+    ]));
+    let traces = [
+        equivalence::trace(&a, &input, limit),
+        equivalence::trace(&b, &input, limit),
+    ];
+    let steps = traces[0].len().max(traces[1].len());
+    for step in 0..steps.min(TRACE_SHOWN) {
+        let column = |trace: &Vec<String>| trace.get(step).cloned().unwrap_or_default();
+        table.add_row(Row::new(vec![
+            Cell::new(&step.to_string()),
+            Cell::new(&column(&traces[0])),
+            Cell::new(&column(&traces[1])),
+        ]));
+    }
+    let results: Vec<String> = [&a, &b]
+        .iter()
+        .map(|machine| {
+            let behaviour = equivalence::behaviour(machine, &input, limit);
+            let output = behaviour.output.concat();
+            format!(
+                "{}, tape '{}'",
+                behaviour.verdict.name(),
+                if output.is_empty() { "ε" } else { &output }
+            )
+        })
+        .collect();
+    table.add_row(Row::new(vec![
+        Cell::new("result"),
+        Cell::new(&results[0]),
+        Cell::new(&results[1]),
+    ]));
+    table.printstd();
+    if steps > TRACE_SHOWN {
+        println!("(showing the first {} of {} steps)", TRACE_SHOWN, steps - 1);
+    }
+    std::process::exit(1);
+}
+
+/// Longest inputs a converted machine is checked on.
+const VERIFY_LENGTH: usize = 4;
+
+fn single_track_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let reachable = args.get(3).is_some_and(|a| a == "reachable");
+    let path = args.get(if reachable { 4 } else { 3 });
+    let (converted, legend) =
+        multitrack::single_track(&turing_machine, reachable).map_err(io::Error::other)?;
+    let checked = multitrack::verify(&turing_machine, &converted, &legend, VERIFY_LENGTH)
+        .map_err(io::Error::other)?;
+
+    let text = format!(
+        "# single-track version of {}, same results on {} input(s) of length 0 to {}\n{}{}",
+        args[2],
+        checked,
+        VERIFY_LENGTH,
+        legend.comment(),
+        definition::write_machine(&converted)
+    );
+    println!("{}", text);
+    println!(
+        "{}",
+        format!(
+            "{} tuple symbol(s), {} rule(s) instead of {}",
+            legend.symbols.len(),
+            converted.transitions.len(),
+            turing_machine.transitions.len()
+        )
+        .green()
+    );
+    if let Some(path) = path {
+        if definition::is_csv(path) {
+            definition::save_machine(path, &converted)?;
+        } else {
+            std::fs::write(path, text)?;
+        }
+        println!("Saved to {}", path);
+    }
+    Ok(())
+}
+
+fn single_tape_behaviour(args: Vec<String>) -> io::Result<()> {
+    let machine = multitape::read_multitape(&args[2]).map_err(io::Error::other)?;
+    let max_length = match args.get(3).map(|n| n.parse::<usize>()) {
+        Some(Ok(n)) => Some(n),
+        _ => None,
+    };
+    let path = args.get(if max_length.is_some() { 4 } else { 3 });
+    let max_length = max_length.unwrap_or(6);
+    let (converted, encoding) = multitape::single_tape(&machine).map_err(io::Error::other)?;
+    let report = multitape::overhead(&machine, &converted, &encoding, max_length)
+        .map_err(io::Error::other)?;
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("Length"),
+        Cell::new("Inputs"),
+        Cell::new(&format!("{}-tape steps", machine.tapes())),
+        Cell::new("Single-tape steps"),
+        Cell::new("Ratio"),
+    ]));
+    for row in &report {
+        let ratio = if row.steps == 0 {
+            String::from("-")
+        } else {
+            format!("{:.1}", row.simulated as f64 / row.steps as f64)
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&row.length.to_string()),
+            Cell::new(&row.inputs.to_string()),
+            Cell::new(&row.steps.to_string()),
+            Cell::new(&row.simulated.to_string()),
+            Cell::new(&ratio),
+        ]));
+    }
+    table.printstd();
+    println!("(most steps taken on an input of each length)");
+    let states = normalize::all_states(&converted).len();
+    println!(
+        "{}",
+        format!(
+            "Same results on every input; {} state(s) and {} rule(s) instead of {} rule(s)",
+            states,
+            converted.transitions.len(),
+            machine.machine.transitions.len()
+        )
+        .green()
+    );
+    if let Some(path) = path {
+        if definition::is_csv(path) {
+            definition::save_machine(path, &converted)?;
+        } else {
+            let text = format!(
+                "# single-tape version of {}\n{}{}",
+                args[2],
+                encoding.comment(),
+                definition::write_machine(&converted)
+            );
+            std::fs::write(path, text)?;
+        }
+        println!("Saved to {}", path);
+    }
+    Ok(())
+}
+
+fn one_way_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let (converted, encoding) = oneway::one_way(&turing_machine).map_err(io::Error::other)?;
+    let checked = oneway::verify(&turing_machine, &converted, &encoding, VERIFY_LENGTH)
+        .map_err(io::Error::other)?;
+
+    let text = format!(
+        "# one-way version of {}, same results on {} input(s) of length 0 to {}\n{}{}",
+        args[2],
+        checked,
+        VERIFY_LENGTH,
+        encoding.comment(),
+        definition::write_machine(&converted)
+    );
+    match args.get(3) {
+        Some(path) => {
+            if definition::is_csv(path) {
+                definition::save_machine(path, &converted)?;
             } else {
-                break;
+                std::fs::write(path, text)?;
             }
+            println!("Saved to {}", path);
         }
+        None => println!("{}", text),
+    }
+    println!(
+        "{}",
+        format!(
+            "Same results on {} input(s); {} rule(s) instead of {}",
+            checked,
+            converted.transitions.len(),
+            turing_machine.transitions.len()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+fn determinize_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let max_length = match args.get(3).map(|n| n.parse::<usize>()) {
+        Some(Ok(n)) => Some(n),
+        _ => None,
+    };
+    let path = args.get(if max_length.is_some() { 4 } else { 3 });
+    let max_length = max_length.unwrap_or(VERIFY_LENGTH);
+    let deterministic = ntm::determinize(&turing_machine).map_err(io::Error::other)?;
+    let report =
+        ntm::verify(&turing_machine, &deterministic, max_length).map_err(io::Error::other)?;
+    let (single, encoding) = multitape::single_tape(&deterministic).map_err(io::Error::other)?;
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("Length"),
+        Cell::new("Inputs"),
+        Cell::new("Nondeterministic steps"),
+        Cell::new("3-tape steps"),
+    ]));
+    for row in &report {
+        table.add_row(Row::new(vec![
+            Cell::new(&row.length.to_string()),
+            Cell::new(&row.inputs.to_string()),
+            Cell::new(&row.depth.to_string()),
+            Cell::new(&row.steps.to_string()),
+        ]));
+    }
+    table.printstd();
+    println!("(most steps taken on an input of each length)");
+    println!(
+        "{}",
+        format!(
+            "Same results on every input; up to {} choice(s) per step",
+            ntm::branching(&turing_machine)
+        )
+        .green()
+    );
+    println!(
+        "{} rule(s), {} as a 3-tape machine, {} as a single-tape machine",
+        turing_machine.transitions.len(),
+        deterministic.machine.transitions.len(),
+        single.transitions.len()
+    );
+    if let Some(path) = path {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".mtm") {
+            std::fs::write(path, multitape::write_multitape(&deterministic))?;
+        } else if lower.ends_with(".csv") {
+            definition::save_machine(path, &single)?;
+        } else {
+            let text = format!(
+                "# deterministic single-tape version of {}\n{}{}",
+                args[2],
+                encoding.comment(),
+                definition::write_machine(&single)
+            );
+            std::fs::write(path, text)?;
+        }
+        println!("Saved to {}", path);
+    }
+    Ok(())
+}
+
+fn minimize_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let (minimized, report) = minimize::minimize(&turing_machine);
+    let checked =
+        minimize::verify(&turing_machine, &minimized, VERIFY_LENGTH).map_err(io::Error::other)?;
+
+    let none = || String::from("-");
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.add_row(Row::new(vec![
+        Cell::new("Unreachable states"),
+        Cell::new(&if report.unreachable.is_empty() {
+            none()
+        } else {
+            report.unreachable.join(", ")
+        }),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("Rules that never fire"),
+        Cell::new(&if report.dead_rules.is_empty() {
+            none()
+        } else {
+            report
+                .dead_rules
+                .iter()
+                .map(|&rule| definition::format_function(&turing_machine.transitions[rule]))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new(&format!("Merged states ({} round(s))", report.rounds)),
+        Cell::new(&if report.merged.is_empty() {
+            none()
+        } else {
+            report
+                .merged
+                .iter()
+                .map(|(name, states)| format!("{} ← {}", name, states.join(", ")))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("Renamed"),
+        Cell::new(
+            &report
+                .names
+                .iter()
+                .map(|(old, new)| format!("{} → {}", old, new))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ]));
+    table.printstd();
+
+    let text = format!(
+        "# minimized version of {}, same results on {} input(s) of length 0 to {}\n{}",
+        args[2],
+        checked,
+        VERIFY_LENGTH,
+        definition::write_machine(&minimized)
+    );
+    match args.get(3) {
+        Some(path) => {
+            if definition::is_csv(path) {
+                definition::save_machine(path, &minimized)?;
+            } else {
+                std::fs::write(path, text)?;
+            }
+            println!("Saved to {}", path);
+        }
+        None => println!("{}", text),
+    }
+    let count = |machine: &Machine| normalize::all_states(machine).len();
+    println!(
+        "{}",
+        format!(
+            "Same results on {} input(s); {} state(s) and {} rule(s) instead of {} and {}",
+            checked,
+            count(&minimized),
+            minimized.transitions.len(),
+            count(&turing_machine),
+            turing_machine.transitions.len()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+fn normalize_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let (normalized, names) = normalize::normalize(&turing_machine);
+    let as_table = args.get(3).is_some_and(|a| a == "table");
+    let path = args.get(if as_table { 4 } else { 3 });
+
+    let text = format!(
+        "# normalized version of {}\n{}{}",
+        args[2],
+        normalize::comment(&names),
+        definition::write_machine(&normalized)
+    );
+    if as_table {
+        print!("{}", normalize::comment(&names));
+        matrix::table(&matrix::matrix(&normalized)).printstd();
+        println!("{}", matrix::LEGEND);
+    }
+    match path {
+        Some(path) => {
+            if definition::is_csv(path) {
+                definition::save_machine(path, &normalized)?;
+            } else {
+                std::fs::write(path, text)?;
+            }
+            println!("Saved to {}", path);
+        }
+        None if !as_table => println!("{}", text),
+        None => {}
+    }
+    Ok(())
+}
+
+fn matrix_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    let turing_machine = load_machine(&args[2], options)?;
+    let matrix = matrix::matrix(&turing_machine);
+    matrix::table(&matrix).printstd();
+    println!("{}", matrix::LEGEND);
+    let conflicts = matrix.count(|entry| matches!(entry, matrix::Entry::Conflict(_)));
+    let missing = matrix.count(|entry| matches!(entry, matrix::Entry::Missing));
+    let summary = format!(
+        "{} state(s) × {} symbol(s), {} conflict(s), {} missing entr{}",
+        matrix.states.len(),
+        matrix.symbols.len(),
+        conflicts,
+        missing,
+        if missing == 1 { "y" } else { "ies" }
+    );
+    if conflicts > 0 {
+        println!("{}", summary.red());
+    } else if missing > 0 {
+        println!("{}", summary.yellow());
     } else {
-        println!("Demo index out of bounds");
+        println!("{}", summary.green());
     }
+
+    if let Some(path) = args.get(3) {
+        let lower = path.to_lowercase();
+        let text = if lower.ends_with(".md") {
+            matrix::markdown(&matrix)
+        } else if lower.ends_with(".csv") {
+            matrix::csv(&matrix)?
+        } else if lower.ends_with(".html") || lower.ends_with(".htm") {
+            matrix::html(&matrix)
+        } else {
+            return Err(io::Error::other(format!(
+                "can't tell the format of '{}', use .md, .csv or .html",
+                path
+            )));
+        };
+        std::fs::write(path, text)?;
+        println!("Saved to {}", path);
+    }
+    Ok(())
 }
 
-fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
-    println!("Turing Machine Simulator");
+fn utm_behaviour(args: Vec<String>, options: &Options) -> io::Result<()> {
+    if args[2] == "encode" {
+        if args.len() < 4 {
+            return Err(io::Error::other("-utm encode needs a machine path"));
+        }
+        let turing_machine = load_machine(&args[3], options)?;
+        let input = args.get(4).map(String::as_str).unwrap_or("");
+        let encoding = utm::encode(&turing_machine, input).map_err(io::Error::other)?;
+        println!("{}", encoding.text);
+        let (states, symbols) = encoding.legend();
+        println!(
+            "{}",
+            format!("states (after the final bit): {}", states).dimmed()
+        );
+        println!("{}", format!("symbols: {}", symbols).dimmed());
+        return Ok(());
+    }
+    if args[2] == "decode" {
+        if args.len() < 4 {
+            return Err(io::Error::other("-utm decode needs an encoding"));
+        }
+        let symbols: Option<Vec<char>> = args.get(4).map(|s| s.chars().collect());
+        let (turing_machine, input) =
+            utm::decode(&args[3], symbols.as_deref()).map_err(io::Error::other)?;
+        let note = if symbols.is_some() {
+            "# states are named by their number, their names aren't encoded"
+        } else {
+            "# states and symbols are named by their number, their names aren't encoded;\n# give the symbols in order (e.g. 01) to get them back"
+        };
+        println!("{}", note.dimmed());
+        print!("{}", definition::write_machine(&turing_machine));
+        println!("input: {}", if input.is_empty() { "ε" } else { &input });
+        return Ok(());
+    }
+    let turing_machine = load_machine(&args[2], options)?;
+    let max_length = match args.get(3) {
+        Some(n) => n
+            .parse::<usize>()
+            .map_err(|_| io::Error::other(format!("'{}' isn't a length", n)))?,
+        None => 3,
+    };
+    let report = utm::cross_check(&turing_machine, max_length).map_err(io::Error::other)?;
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(vec![
+        Cell::new("Length"),
+        Cell::new("Inputs"),
+        Cell::new("Native steps"),
+        Cell::new("UTM steps"),
+    ]));
+    for row in &report {
+        table.add_row(Row::new(vec![
+            Cell::new(&row.length.to_string()),
+            Cell::new(&row.inputs.to_string()),
+            Cell::new(&row.steps.to_string()),
+            Cell::new(&row.simulated.to_string()),
+        ]));
+    }
+    table.printstd();
+    println!("(most steps taken on an input of each length)");
+    println!(
+        "{}",
+        format!(
+            "Same verdicts and tapes on {} input(s)",
+            report.iter().map(|row| row.inputs).sum::<usize>()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+/// Loads a machine from a CSV file (asking for what CSV can't hold) or a
+/// machine definition file.
+fn load_machine(path: &str, options: &Options) -> io::Result<Machine> {
+    let mut turing_machine = if definition::is_csv(path) {
+        read_csv(path)?
+    } else {
+        definition::read_machine(path)?
+    };
+    if let Some(mode) = options.tape_mode {
+        turing_machine.tape_mode = mode;
+    }
+    Ok(turing_machine)
+}
+
+fn read_csv(file_path: &str) -> io::Result<Machine> {
     print!("Number of tracks: ");
     io::stdout().flush().expect("failed to flush");
 
-    let file_path = args[2].clone();
     let file = File::open(file_path)?;
     let mut rdr = Reader::from_reader(file);
     let headers = rdr.headers()?;
@@ -216,7 +1145,7 @@ fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
     ]);
     if headers.clone() != valid_headers {
         println!("Invalid headers.\n Headers must be \"lhs_state\", \"input\", \"rhs_state\", \"replacement\", \"direction\"");
-        return Err(io::Error::new(io::ErrorKind::Other, "Invalid headers"));
+        return Err(io::Error::other("Invalid headers"));
     }
 
     let tracks: usize = get_input().trim().parse().unwrap();
@@ -243,30 +1172,25 @@ fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
         if direction.len() != 1 {
             println!("Invalid Direction (Direction is more than a character)");
             println!("{:?}", record);
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Invalid Data in direction field",
-            ));
+            return Err(io::Error::other("Invalid Data in direction field"));
         }
-        if (input != "□".repeat(tracks) && input.len() != tracks)
-            || (replacement != "□".repeat(tracks) && replacement.len() != tracks)
-        {
-            println!("Symbol length does not match number of tracks");
+        let direction_char: Vec<char> = direction[0].to_uppercase().collect();
+        let lhs = LHS {
+            state: lhs_state.to_string(),
+            input: input.to_string(),
+        };
+        let rhs = RHS {
+            state: rhs_state.to_string(),
+            replacement: replacement.to_string(),
+            direction: direction_char[0],
+        };
+        let current_function = TransitionFunction { lhs, rhs };
+        if let Err(e) = rules::check(&current_function, tracks, &[]) {
+            println!("Invalid symbols: {}", e);
             println!("{:?}", record);
-            return Err(io::Error::new(io::ErrorKind::Other, "Mismatch length"));
+            return Err(io::Error::other("Mismatch length"));
         }
-        let direction_char: Vec<char> = direction[0].to_uppercase().collect();
         if direction_char[0] == 'R' || direction_char[0] == 'L' {
-            let lhs = LHS {
-                state: lhs_state.to_string(),
-                input: input.to_string(),
-            };
-            let rhs = RHS {
-                state: rhs_state.to_string(),
-                replacement: replacement.to_string(),
-                direction: direction_char[0],
-            };
-            let current_function = TransitionFunction { lhs, rhs };
             println!(
                 "Transition function: δ({},{})=({},{},{})",
                 current_function.lhs.state,
@@ -279,31 +1203,35 @@ fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
         } else {
             println!("Invalid Direction (Direction is not L or R)");
             println!("{:?}", record);
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Invalid Data in direction field",
-            ));
+            return Err(io::Error::other("Invalid Data in direction field"));
         }
     }
     let key_states = get_states(&functions);
-    let turing_machine = Machine {
+    let (input_alphabet, tape_alphabet) = get_alphabets();
+    Ok(Machine {
         transitions: functions,
-        tracks: tracks,
+        tracks,
         states: key_states,
-    };
+        tape_mode: TapeMode::TwoWay,
+        sets: Vec::new(),
+        input_alphabet,
+        tape_alphabet,
+    })
+}
 
+fn run_inputs(turing_machine: &Machine, options: &Options) {
     loop {
         print!("Track 1: ");
         io::stdout().flush().expect("failed to flush");
         let mut inputs: Vec<String> = vec![get_input().trim().to_string().replace("\r\n", "")];
 
         // Get the remaining inputs, checking if they have the same length as the first one
-        for i in 1..tracks {
+        for i in 1..turing_machine.tracks {
             loop {
-                print!("Tape (Track {}): ", i + 1);
+                print!("Track {}: ", i + 1);
                 io::stdout().flush().expect("failed to flush");
                 let input = get_input().trim().to_string().replace("\r\n", "");
-                if input.len() == inputs[0].len() {
+                if input.chars().count() == inputs[0].chars().count() {
                     inputs.push(input);
                     break;
                 } else {
@@ -312,21 +1240,7 @@ fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
             }
         }
 
-        // Combine the inputs
-        let combined: String = (0..inputs[0].len())
-            .map(|i| {
-                inputs
-                    .iter()
-                    .map(|s| s.chars().nth(i).unwrap())
-                    .collect::<String>()
-            })
-            .collect();
-        parse(
-            combined,
-            &turing_machine.transitions,
-            &turing_machine.states,
-            turing_machine.tracks,
-        );
+        run_input(combine_tracks(&inputs), turing_machine, options);
         println!("Parse another string? (Y/N)");
         if get_input()
             .trim()
@@ -340,15 +1254,72 @@ fn csv_behaviour(args: Vec<String>) -> io::Result<()> {
             break;
         }
     }
+}
 
-    Ok(())
+/// Asks for a file to save the machine to. Returns whether it was saved.
+fn offer_save(turing_machine: &Machine) -> bool {
+    loop {
+        println!("Save this machine? Enter a file name (.tm, or .csv for the rules only) or leave empty to skip:");
+        let path = get_input().trim().to_string();
+        if path.is_empty() {
+            return false;
+        }
+        match definition::save_machine(&path, turing_machine) {
+            Ok(()) => {
+                println!("Saved to {}", path);
+                return true;
+            }
+            Err(e) => println!("{}", format!("Error: {}", e).red()),
+        }
+    }
+}
+
+/// Interleaves the tracks into one string holding one character per track for
+/// every cell.
+fn combine_tracks(inputs: &[String]) -> String {
+    (0..inputs[0].chars().count())
+        .map(|i| {
+            inputs
+                .iter()
+                .map(|s| s.chars().nth(i).unwrap())
+                .collect::<String>()
+        })
+        .collect()
+}
+
+fn run_input(input: String, turing_machine: &Machine, options: &Options) {
+    if let Some(sigma) = &turing_machine.input_alphabet {
+        if let Some(c) = input.chars().find(|c| !sigma.contains(c)) {
+            println!(
+                "{}",
+                format!(
+                    "Error: '{}' is not in the input alphabet Σ = {}",
+                    c,
+                    rules::format_symbols(sigma)
+                )
+                .red()
+            );
+            return;
+        }
+    }
+    if options.debug {
+        debugger::debug(turing_machine, &input);
+    } else {
+        parse(input, turing_machine);
+    }
 }
 
-fn get_transitions(chunk: usize) -> Vec<TransitionFunction> {
+fn get_transitions(chunk: usize) -> (Vec<TransitionFunction>, Vec<SymbolSet>) {
     let mut functions = Vec::new();
+    let mut sets: Vec<SymbolSet> = Vec::new();
 
     println!("Enter functions e.g δ(q1,a)=(q2,b,L) [enter 'END' if you don't want to add anymore functions]: ");
     println!("*you can use 'blank' instead of □");
+    println!(
+        "*'?' as input matches any symbol, '?' as replacement writes back the symbol that was read"
+    );
+    println!(" (use it per track on multi-track cells, e.g. a? or ?b)");
+    println!("*declare symbol sets e.g. digit = {{0..9}} and use them as input e.g. δ(q1,[d∈digit])=(q1,d,R)");
 
     loop {
         print!("δ");
@@ -358,48 +1329,37 @@ fn get_transitions(chunk: usize) -> Vec<TransitionFunction> {
             break;
         }
 
+        if definition::is_set_declaration(&func) {
+            match rules::parse_set(&func) {
+                Ok(set) => {
+                    sets.retain(|s| s.name != set.name);
+                    sets.push(set);
+                }
+                Err(e) => println!("invalid set... {} (set was not added)", e),
+            }
+            continue;
+        }
+
         let func = func.replace(" ", "");
         if !function_validator(&func) {
             println!("invalid format... (function was not added)");
             continue;
         }
-        let func = func.replace("blank", "□");
-        let parts: Vec<&str> = func.split('=').collect();
-
-        let lhs_parts: Vec<&str> = parts[0]
-            .trim_matches(|c| c == '(' || c == ')')
-            .split(',')
-            .map(|s| s.trim())
-            .collect();
-
-        let lhs = LHS {
-            state: lhs_parts[0].to_string(),
-            input: lhs_parts[1].to_string(),
-        };
-
-        let rhs_parts: Vec<&str> = parts[1]
-            .trim_matches(|c| c == '(' || c == ')' || c == '\r' || c == '\n')
-            .split(',')
-            .map(|s| s.trim())
-            .collect();
-
-        let rhs = RHS {
-            state: rhs_parts[0].to_string(),
-            replacement: rhs_parts[1].to_string(),
-            direction: rhs_parts[2].to_uppercase().chars().next().unwrap(),
+        let current_function = match definition::parse_function(&func) {
+            Ok(function) => function,
+            Err(e) => {
+                println!("invalid format... {} (function was not added)", e);
+                continue;
+            }
         };
-
-        if (lhs.input != "□".repeat(chunk) && lhs.input.len() != chunk)
-            || (rhs.replacement != "□".repeat(chunk) && rhs.replacement.len() != chunk)
-        {
-            println!("invalid format... length mismatch (function was not added)");
+        if let Err(e) = rules::check(&current_function, chunk, &sets) {
+            println!("invalid format... {} (function was not added)", e);
             continue;
         }
-        let current_function = TransitionFunction { lhs, rhs };
         functions.push(current_function);
     }
 
-    functions
+    (functions, sets)
 }
 
 fn get_states(transitions: &[TransitionFunction]) -> KeyStates {
@@ -430,28 +1390,45 @@ fn get_states(transitions: &[TransitionFunction]) -> KeyStates {
             println!("Invalid final state")
         }
     }
-    let states = KeyStates {
+    KeyStates {
         initial_state: initial,
         final_states: finals,
-    };
+    }
+}
 
-    states
+fn get_alphabets() -> (Option<Vec<char>>, Option<Vec<char>>) {
+    let mut alphabets = Vec::new();
+    for name in [
+        "input alphabet Σ e.g. {a, b}",
+        "tape alphabet Γ e.g. {a, b, x, □}",
+    ] {
+        println!("Enter {} [leave empty to not declare one]:", name);
+        loop {
+            let text = get_input().trim().to_string();
+            if text.is_empty() {
+                alphabets.push(None);
+                break;
+            }
+            match rules::parse_symbols(&text) {
+                Ok(symbols) => {
+                    alphabets.push(Some(symbols));
+                    break;
+                }
+                Err(e) => println!("Invalid alphabet: {}", e),
+            }
+        }
+    }
+    let tape_alphabet = alphabets.pop().unwrap();
+    let input_alphabet = alphabets.pop().unwrap();
+    (input_alphabet, rules::with_blank(tape_alphabet))
 }
 
-fn parse(input: String, transitions: &[TransitionFunction], states: &KeyStates, chunk: usize) {
+fn parse(input: String, machine: &Machine) {
+    let chunk = machine.tracks;
+    let transitions = &machine.transitions;
+    let one_way = machine.tape_mode.is_one_way();
     println!("\nparsing...");
-    let mut input: Vec<String> = input
-        .chars()
-        .collect::<String>()
-        .chars()
-        .collect::<Vec<char>>()
-        .chunks(chunk)
-        .map(|chunk| chunk.iter().collect::<String>())
-        .collect();
-    input.push(String::from("□").repeat(chunk));
-    input.insert(0, String::from("□").repeat(chunk));
-    let mut i = 1;
-    let mut current_state = states.initial_state.clone();
+    let mut run = Run::new(machine, &input);
 
     loop {
         let mut table = Table::new();
@@ -474,19 +1451,24 @@ fn parse(input: String, transitions: &[TransitionFunction], states: &KeyStates,
                 .padding(1, 1)
                 .build(),
         );
-        let current_input = input[i].clone();
 
         let mut head_row = Row::new(vec![Cell::new("HEAD")]);
-        for (index, _) in input.iter().enumerate() {
-            let head_symbol = if index == i { "▼" } else { " " };
+        if one_way {
+            head_row.add_cell(Cell::new(" "));
+        }
+        for position in run.tape.span() {
+            let head_symbol = if position == run.head { "▼" } else { " " };
             head_row.add_cell(Cell::new(head_symbol));
         }
         table.add_row(head_row);
 
         for n in 0..chunk {
             let mut tape_row = Row::new(vec![Cell::new("TAPE")]);
-            for s in &input {
-                let char_at_index = s.chars().nth(n);
+            if one_way {
+                tape_row.add_cell(Cell::new("⊢"));
+            }
+            for position in run.tape.span() {
+                let char_at_index = run.cell(position).chars().nth(n);
                 if let Some(c) = char_at_index {
                     tape_row.add_cell(Cell::new(c.to_string().as_str()));
                 }
@@ -496,48 +1478,63 @@ fn parse(input: String, transitions: &[TransitionFunction], states: &KeyStates,
 
         table.printstd();
 
-        let lhs_to_find = LHS {
-            state: current_state.clone(),
-            input: current_input.clone(),
+        println!("Current state: {}", run.state);
+        println!(
+            "Current input: '{}', Head position: {}",
+            run.current(),
+            run.head
+        );
+        let Some(step) = run.step() else {
+            break;
         };
-        println!("Current state: {}", current_state);
-        println!("Current input: '{}', Head position: {}", current_input, i);
-        if let Some(transition) = transitions.iter().find(|t| t.lhs == lhs_to_find) {
-            println!(
-                "Transition function: δ({},{})=({},{},{})\n",
-                transition.lhs.state,
-                transition.lhs.input,
-                transition.rhs.state,
-                transition.rhs.replacement,
-                transition.rhs.direction
-            );
-            current_state = transition.rhs.state.clone();
-            input[i] = transition.rhs.replacement.clone();
-            if transition.rhs.direction == 'L' {
-                if i == 0 {
-                    input.insert(0, String::from("□").repeat(chunk));
-                } else {
-                    i = i - 1;
-                }
-            } else if transition.rhs.direction == 'R' {
-                i = i + 1;
-                if i == input.len() {
-                    input.push(String::from("□").repeat(chunk));
-                }
-            }
-        } else {
+        println!(
+            "Transition function: {}\n",
+            definition::format_function(&transitions[step.rule])
+        );
+        if step.stayed {
+            println!("Head is at the left end of the tape and stays put\n");
+        }
+        if run.status == Status::Crashed {
             break;
         }
     }
 
-    if states.final_states.contains(&current_state) {
-        println!("{}", "Success".green());
-    } else {
-        println!("{}", "Failure".red());
+    match run.status {
+        Status::Crashed => {
+            println!(
+                "{}",
+                "Crashed: head moved off the left end of the tape".red()
+            );
+            println!("{}", "Failure".red());
+        }
+        Status::Accepted => println!("{}", "Success".green()),
+        _ => println!("{}", "Failure".red()),
     }
 }
 
 // helper functions
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let Some(position) = args.iter().position(|arg| arg == flag) else {
+        return false;
+    };
+    args.remove(position);
+    true
+}
+
+fn take_tape_mode(args: &mut Vec<String>) -> Result<Option<TapeMode>, String> {
+    let Some(position) = args.iter().position(|arg| arg == "-tape") else {
+        return Ok(None);
+    };
+    if position + 1 >= args.len() {
+        return Err(String::from("-tape expects a mode"));
+    }
+    let name = args.remove(position + 1);
+    args.remove(position);
+    match TapeMode::from_name(&name) {
+        Some(mode) => Ok(Some(mode)),
+        None => Err(format!("Unknown tape mode '{}'", name)),
+    }
+}
 fn function_validator(function: &str) -> bool {
     let re = Regex::new(r"\(.*\,(.*)\)\=\(.*\,(.*)\,(L|R|l|r)\)").unwrap();
     re.is_match(function)
@@ -554,677 +1551,3 @@ fn get_input() -> String {
     io::stdin().read_line(&mut input).expect("error occurred");
     input
 }
-
-// demos
-fn demos() -> Vec<Machine> {
-    let f1 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("a"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("b"),
-            direction: 'R',
-        },
-    };
-
-    let f2 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("b"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("b"),
-            direction: 'R',
-        },
-    };
-
-    let f3 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("□"),
-            direction: 'L',
-        },
-    };
-
-    let s1 = KeyStates {
-        initial_state: String::from("q0"),
-        final_states: vec![String::from("q1")],
-    };
-    let functions_translator = vec![f1, f2, f3];
-
-    let demo0 = Machine {
-        transitions: functions_translator,
-        states: s1,
-        tracks: 1,
-    };
-
-    let f4 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("a"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("x"),
-            direction: 'R',
-        },
-    };
-
-    let f5 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("a"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("a"),
-            direction: 'R',
-        },
-    };
-
-    let f6 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("y"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("y"),
-            direction: 'R',
-        },
-    };
-
-    let f7 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("b"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("y"),
-            direction: 'L',
-        },
-    };
-
-    let f8 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("y"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("y"),
-            direction: 'L',
-        },
-    };
-
-    let f9 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("a"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("a"),
-            direction: 'L',
-        },
-    };
-
-    let f10 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("x"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("x"),
-            direction: 'R',
-        },
-    };
-    let f11 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("y"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("y"),
-            direction: 'R',
-        },
-    };
-
-    let f12 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q3"),
-            input: String::from("y"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("y"),
-            direction: 'R',
-        },
-    };
-
-    let f13 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q3"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q4"),
-            replacement: String::from("□"),
-            direction: 'L',
-        },
-    };
-
-    let s2 = KeyStates {
-        initial_state: String::from("q0"),
-        final_states: vec![String::from("q4")],
-    };
-
-    let functions_accepter = vec![f4, f5, f6, f7, f8, f9, f10, f11, f12, f13];
-
-    let demo1 = Machine {
-        transitions: functions_accepter,
-        states: s2,
-        tracks: 1,
-    };
-
-    let f14 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("x"),
-            direction: 'R',
-        },
-    };
-
-    let f15 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("□"),
-            direction: 'L',
-        },
-    };
-
-    let f16 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-
-    let f17 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("□"),
-            direction: 'R',
-        },
-    };
-
-    let f18 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("x"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-
-    let f19 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-
-    let f20 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-
-    let s3 = KeyStates {
-        initial_state: String::from("q0"),
-        final_states: vec![String::from("q3")],
-    };
-
-    let functions_copier = vec![f14, f15, f16, f17, f18, f19, f20];
-
-    let demo2 = Machine {
-        transitions: functions_copier,
-        states: s3,
-        tracks: 1,
-    };
-    let f21 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("aa"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("11"),
-            direction: 'R',
-        },
-    };
-
-    let f22 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("bb"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("11"),
-            direction: 'R',
-        },
-    };
-
-    let f23 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("ab"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("ab"),
-            direction: 'R',
-        },
-    };
-    let f24 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("ba"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("ba"),
-            direction: 'R',
-        },
-    };
-    let f25 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("□□"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("□□"),
-            direction: 'L',
-        },
-    };
-
-    let s4 = KeyStates {
-        initial_state: String::from("q0"),
-        final_states: vec![String::from("q1")],
-    };
-    let functions_translator1 = vec![f21, f22, f23, f24, f25];
-
-    let demo3 = Machine {
-        transitions: functions_translator1,
-        states: s4,
-        tracks: 2,
-    };
-    // ref: https://www.geeksforgeeks.org/turing-machine-for-multiplication/
-    let f26 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q0"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-    let f27 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q0"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("*"),
-            direction: 'R',
-        },
-    };
-    let f28 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q1"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-    let f29 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q1"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("*"),
-            direction: 'L',
-        },
-    };
-    let f30 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q2"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-    let f31 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q2"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("*"),
-            direction: 'R',
-        },
-    };
-    let f32 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q3"),
-            input: String::from("X"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("X"),
-            direction: 'R',
-        },
-    };
-    let f33 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q3"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q4"),
-            replacement: String::from("X"),
-            direction: 'L',
-        },
-    };
-    let f34 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q3"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q12"),
-            replacement: String::from("□"),
-            direction: 'R',
-        },
-    };
-    let f35 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q4"),
-            input: String::from("X"),
-        },
-        rhs: RHS {
-            state: String::from("q4"),
-            replacement: String::from("X"),
-            direction: 'L',
-        },
-    };
-    let f36 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q4"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q5"),
-            replacement: String::from("*"),
-            direction: 'L',
-        },
-    };
-    let f37 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q5"),
-            input: String::from("Y"),
-        },
-        rhs: RHS {
-            state: String::from("q5"),
-            replacement: String::from("Y"),
-            direction: 'L',
-        },
-    };
-    let f38 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q5"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q6"),
-            replacement: String::from("Y"),
-            direction: 'R',
-        },
-    };
-    let f39 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q5"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q11"),
-            replacement: String::from("□"),
-            direction: 'R',
-        },
-    };
-    let f40 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q6"),
-            input: String::from("Y"),
-        },
-        rhs: RHS {
-            state: String::from("q6"),
-            replacement: String::from("Y"),
-            direction: 'R',
-        },
-    };
-    let f41 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q6"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q7"),
-            replacement: String::from("*"),
-            direction: 'R',
-        },
-    };
-    let f42 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q7"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q7"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-    let f43 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q7"),
-            input: String::from("X"),
-        },
-        rhs: RHS {
-            state: String::from("q7"),
-            replacement: String::from("X"),
-            direction: 'R',
-        },
-    };
-    let f44 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q7"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q8"),
-            replacement: String::from("*"),
-            direction: 'R',
-        },
-    };
-    let f45 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q8"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q8"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-    let f46 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q8"),
-            input: String::from("□"),
-        },
-        rhs: RHS {
-            state: String::from("q9"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-    let f47 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q9"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q9"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-    let f48 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q9"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q10"),
-            replacement: String::from("*"),
-            direction: 'L',
-        },
-    };
-    let f49 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q10"),
-            input: String::from("1"),
-        },
-        rhs: RHS {
-            state: String::from("q10"),
-            replacement: String::from("1"),
-            direction: 'L',
-        },
-    };
-    let f50 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q10"),
-            input: String::from("X"),
-        },
-        rhs: RHS {
-            state: String::from("q10"),
-            replacement: String::from("X"),
-            direction: 'L',
-        },
-    };
-    let f51 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q10"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q5"),
-            replacement: String::from("*"),
-            direction: 'L',
-        },
-    };
-    let f52 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q11"),
-            input: String::from("Y"),
-        },
-        rhs: RHS {
-            state: String::from("q11"),
-            replacement: String::from("1"),
-            direction: 'R',
-        },
-    };
-    let f53 = TransitionFunction {
-        lhs: LHS {
-            state: String::from("q11"),
-            input: String::from("*"),
-        },
-        rhs: RHS {
-            state: String::from("q3"),
-            replacement: String::from("*"),
-            direction: 'R',
-        },
-    };
-    let s5 = KeyStates {
-        initial_state: String::from("q0"),
-        final_states: vec![String::from("q12")],
-    };
-    let functions_mult = vec![
-        f26, f27, f28, f29, f30, f31, f32, f33, f34, f35, f36, f37, f38, f39, f40, f41, f42, f43,
-        f44, f45, f46, f47, f48, f49, f50, f51, f52, f53,
-    ];
-    let demo4 = Machine {
-        transitions: functions_mult,
-        states: s5,
-        tracks: 1,
-    };
-    let demos = vec![demo0, demo1, demo2, demo3, demo4];
-
-    demos
-}
@@ -0,0 +1,258 @@
+use crate::normalize;
+use crate::rules::{self, RuleIndex};
+use crate::Machine;
+use csv::Writer;
+use prettytable::{format, Cell, Row, Table};
+use std::io;
+
+// The transition table as a state × symbol matrix over every cell the
+// machine can read, whatever mix of literal, set and wildcard rules covers
+// it.
+
+/// What a state does with one cell.
+pub enum Entry {
+    /// The rule that fires, as '(p,b,D)'.
+    Rule(String),
+    /// Rules that are equally specific but do different things, the one that
+    /// fires first.
+    Conflict(Vec<String>),
+    /// No rule, in a state that isn't final.
+    Missing,
+    /// No rule, in a final state.
+    Halt,
+}
+
+pub struct Matrix {
+    pub symbols: Vec<String>,
+    pub states: Vec<String>,
+    pub entries: Vec<Vec<Entry>>,
+    initial: String,
+    finals: Vec<String>,
+}
+
+impl Matrix {
+    /// The state's row label: → for the initial state and * for final ones.
+    fn label(&self, state: &str) -> String {
+        let mut label = String::new();
+        if state == self.initial {
+            label.push('→');
+        }
+        if self.finals.iter().any(|f| f == state) {
+            label.push('*');
+        }
+        label + state
+    }
+
+    pub fn is_final(&self, state: &str) -> bool {
+        self.finals.iter().any(|f| f == state)
+    }
+
+    pub fn count(&self, f: fn(&Entry) -> bool) -> usize {
+        self.entries
+            .iter()
+            .flatten()
+            .filter(|entry| f(entry))
+            .count()
+    }
+}
+
+pub fn matrix(machine: &Machine) -> Matrix {
+    let index = RuleIndex::new(machine);
+    let symbols = normalize::cells(machine);
+    let states = normalize::states(machine);
+    let specificity = |rule: usize| {
+        rules::parse_pattern(
+            &machine.transitions[rule].lhs.input,
+            machine.tracks,
+            &machine.sets,
+        )
+        .map(|patterns| rules::specificity(&patterns))
+        .ok()
+    };
+    let entries = states
+        .iter()
+        .map(|state| {
+            symbols
+                .iter()
+                .map(|cell| {
+                    let Some((fired, written)) = index.fire(state, cell) else {
+                        return if machine.states.final_states.contains(state) {
+                            Entry::Halt
+                        } else {
+                            Entry::Missing
+                        };
+                    };
+                    let shown = |rule: usize, written: &str| {
+                        let rhs = &machine.transitions[rule].rhs;
+                        format!("({},{},{})", rhs.state, written, rhs.direction)
+                    };
+                    let first = shown(fired, &written);
+                    // the rules the one that fires only wins by coming first
+                    let mut rivals: Vec<String> = Vec::new();
+                    for (rule, function) in machine.transitions.iter().enumerate() {
+                        if rule == fired
+                            || function.lhs.state != *state
+                            || specificity(rule) != specificity(fired)
+                        {
+                            continue;
+                        }
+                        if let Some(written) = rules::apply(function, machine, cell) {
+                            let other = shown(rule, &written);
+                            if other != first && !rivals.contains(&other) {
+                                rivals.push(other);
+                            }
+                        }
+                    }
+                    if rivals.is_empty() {
+                        Entry::Rule(first)
+                    } else {
+                        rivals.insert(0, first);
+                        Entry::Conflict(rivals)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Matrix {
+        symbols,
+        states,
+        entries,
+        initial: machine.states.initial_state.clone(),
+        finals: machine.states.final_states.clone(),
+    }
+}
+
+/// The matrix for the terminal, with conflicts in red, missing entries in
+/// yellow and final states in green.
+pub fn table(matrix: &Matrix) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    let mut titles = vec![Cell::new("δ")];
+    titles.extend(matrix.symbols.iter().map(|symbol| Cell::new(symbol)));
+    table.set_titles(Row::new(titles));
+    for (state, entries) in matrix.states.iter().zip(&matrix.entries) {
+        let label = Cell::new(&matrix.label(state));
+        let mut row = vec![if matrix.is_final(state) {
+            label.style_spec("bFg")
+        } else {
+            label
+        }];
+        for entry in entries {
+            row.push(match entry {
+                Entry::Rule(rule) => Cell::new(rule),
+                Entry::Conflict(rules) => Cell::new(&rules.join("\n")).style_spec("bFr"),
+                Entry::Missing => Cell::new("-").style_spec("Fy"),
+                Entry::Halt => Cell::new(""),
+            });
+        }
+        table.add_row(Row::new(row));
+    }
+    table
+}
+
+pub const LEGEND: &str =
+    "→ initial state, * final state, conflicting rules in bold (the first one fires), - no rule";
+
+pub fn markdown(matrix: &Matrix) -> String {
+    let escape = |text: &str| text.replace('|', "\\|").replace('*', "\\*");
+    let mut text = format!(
+        "| δ | {} |\n|---|{}\n",
+        matrix
+            .symbols
+            .iter()
+            .map(|symbol| escape(symbol))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        "---|".repeat(matrix.symbols.len())
+    );
+    for (state, entries) in matrix.states.iter().zip(&matrix.entries) {
+        let label = escape(&matrix.label(state));
+        let mut cells = vec![if matrix.is_final(state) {
+            format!("**{}**", label)
+        } else {
+            label
+        }];
+        for entry in entries {
+            cells.push(match entry {
+                Entry::Rule(rule) => escape(rule),
+                Entry::Conflict(rules) => format!("**{}**", escape(&rules.join(" "))),
+                Entry::Missing => String::from("-"),
+                Entry::Halt => String::new(),
+            });
+        }
+        text += &format!("| {} |\n", cells.join(" | "));
+    }
+    text + &format!("\n{}\n", LEGEND)
+}
+
+/// The matrix as CSV, with conflicts as 'conflict: ...' and missing entries
+/// as 'missing'.
+pub fn csv(matrix: &Matrix) -> io::Result<String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    let mut titles = vec![String::from("state")];
+    titles.extend(matrix.symbols.iter().cloned());
+    writer.write_record(&titles)?;
+    for (state, entries) in matrix.states.iter().zip(&matrix.entries) {
+        let mut record = vec![matrix.label(state)];
+        for entry in entries {
+            record.push(match entry {
+                Entry::Rule(rule) => rule.clone(),
+                Entry::Conflict(rules) => format!("conflict: {}", rules.join(" ")),
+                Entry::Missing => String::from("missing"),
+                Entry::Halt => String::new(),
+            });
+        }
+        writer.write_record(&record)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    String::from_utf8(bytes).map_err(io::Error::other)
+}
+
+pub fn html(matrix: &Matrix) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let mut text = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n\
+         table { border-collapse: collapse; font-family: monospace; }\n\
+         th, td { border: 1px solid #999; padding: 2px 8px; text-align: center; }\n\
+         td.conflict { background: #f8d7da; font-weight: bold; }\n\
+         td.missing { background: #fff3cd; }\n\
+         tr.final th { background: #d4edda; }\n\
+         </style>\n</head>\n<body>\n<table>\n",
+    );
+    text += "<tr><th>δ</th>";
+    for symbol in &matrix.symbols {
+        text += &format!("<th>{}</th>", escape(symbol));
+    }
+    text += "</tr>\n";
+    for (state, entries) in matrix.states.iter().zip(&matrix.entries) {
+        text += if matrix.is_final(state) {
+            "<tr class=\"final\">"
+        } else {
+            "<tr>"
+        };
+        text += &format!("<th>{}</th>", escape(&matrix.label(state)));
+        for entry in entries {
+            text += &match entry {
+                Entry::Rule(rule) => format!("<td>{}</td>", escape(rule)),
+                Entry::Conflict(rules) => format!(
+                    "<td class=\"conflict\">{}</td>",
+                    rules
+                        .iter()
+                        .map(|rule| escape(rule))
+                        .collect::<Vec<_>>()
+                        .join("<br>")
+                ),
+                Entry::Missing => String::from("<td class=\"missing\">-</td>"),
+                Entry::Halt => String::from("<td></td>"),
+            };
+        }
+        text += "</tr>\n";
+    }
+    text + &format!("</table>\n<p>{}</p>\n</body>\n</html>\n", escape(LEGEND))
+}
@@ -0,0 +1,158 @@
+use crate::multitrack;
+use crate::normalize::{self, all_states, reachable};
+use crate::oracle::{self, Outcome};
+use crate::rules::RuleIndex;
+use crate::Machine;
+use std::collections::{HashMap, HashSet};
+
+// Minimizing works on what each state does with each cell, whatever mix of
+// literal, set and wildcard rules says so. States the initial state can't
+// reach are dropped along with rules that can never fire, then states are
+// split into classes, first by whether they are final and then by what they
+// write, where they move and which class they go to on each cell, until no
+// class splits any more. The states of one class run alike step for step, so
+// keeping one of them changes neither the verdicts nor the tapes, nor even
+// the number of steps.
+
+/// What changed while minimizing, with states by their original names.
+pub struct Report {
+    pub unreachable: Vec<String>,
+    /// The rules removed because they can never fire, by index.
+    pub dead_rules: Vec<usize>,
+    /// Each new state that stands for more than one original state.
+    pub merged: Vec<(String, Vec<String>)>,
+    /// Rounds of splitting it took to reach the fixpoint.
+    pub rounds: usize,
+    pub names: Vec<(String, String)>,
+}
+
+/// What a state does with one cell: the class it goes to, what it writes and
+/// where it moves, or nothing if it halts.
+type Move = Option<(usize, String, char)>;
+
+pub fn minimize(machine: &Machine) -> (Machine, Report) {
+    let index = RuleIndex::new(machine);
+    let cells = normalize::cells(machine);
+    let (states, fired) = reachable(machine, &index, &cells);
+    let unreachable: Vec<String> = all_states(machine)
+        .into_iter()
+        .filter(|state| !states.contains(state))
+        .collect();
+    let dead_rules: Vec<usize> = (0..fired.len()).filter(|&rule| !fired[rule]).collect();
+
+    // split the states until every class keeps together
+    let mut class: HashMap<&String, usize> = states
+        .iter()
+        .map(|state| {
+            let accepting = machine.states.final_states.contains(state);
+            (state, usize::from(accepting))
+        })
+        .collect();
+    let mut count = class.values().collect::<HashSet<_>>().len();
+    let mut rounds = 0;
+    loop {
+        rounds += 1;
+        let mut signatures: Vec<(usize, Vec<Move>)> = Vec::new();
+        let mut split: HashMap<&String, usize> = HashMap::new();
+        for state in &states {
+            let behaviour = cells
+                .iter()
+                .map(|cell| {
+                    index.fire(state, cell).map(|(rule, written)| {
+                        let rhs = &machine.transitions[rule].rhs;
+                        (class[&rhs.state], written, rhs.direction)
+                    })
+                })
+                .collect();
+            let signature = (class[state], behaviour);
+            let id = match signatures.iter().position(|s| *s == signature) {
+                Some(id) => id,
+                None => {
+                    signatures.push(signature);
+                    signatures.len() - 1
+                }
+            };
+            split.insert(state, id);
+        }
+        class = split;
+        if signatures.len() == count {
+            break;
+        }
+        count = signatures.len();
+    }
+
+    // keep the first state of each class, with the rules of its own that fire
+    let representative = |state: &String| -> String {
+        states
+            .iter()
+            .find(|other| class[other] == class[state])
+            .unwrap()
+            .clone()
+    };
+    let mut minimized = machine.clone();
+    minimized.transitions = machine
+        .transitions
+        .iter()
+        .enumerate()
+        .filter(|(rule, function)| {
+            fired[*rule] && representative(&function.lhs.state) == function.lhs.state
+        })
+        .map(|(_, function)| {
+            let mut function = function.clone();
+            function.rhs.state = representative(&function.rhs.state);
+            function
+        })
+        .collect();
+    minimized.states.final_states = machine
+        .states
+        .final_states
+        .iter()
+        .filter(|state| states.contains(state) && representative(state) == **state)
+        .cloned()
+        .collect();
+
+    let (minimized, names) = normalize::rename(&minimized);
+    let new_name = |state: &String| -> String {
+        let kept = representative(state);
+        names
+            .iter()
+            .find(|(old, _)| *old == kept)
+            .map(|(_, new)| new.clone())
+            .unwrap()
+    };
+    let mut merged: Vec<(String, Vec<String>)> = Vec::new();
+    for state in &states {
+        let name = new_name(state);
+        match merged.iter_mut().find(|(new, _)| *new == name) {
+            Some((_, members)) => members.push(state.clone()),
+            None => merged.push((name, vec![state.clone()])),
+        }
+    }
+    merged.retain(|(_, members)| members.len() > 1);
+    let names = states
+        .iter()
+        .map(|state| (state.clone(), new_name(state)))
+        .collect();
+    (
+        minimized,
+        Report {
+            unreachable,
+            dead_rules,
+            merged,
+            rounds,
+            names,
+        },
+    )
+}
+
+/// Runs both machines on every input up to `max_length` cells with
+/// `oracle::agree`, and returns how many inputs were checked.
+pub fn verify(machine: &Machine, minimized: &Machine, max_length: usize) -> Result<usize, String> {
+    let tape = |tracks: Vec<String>| tracks.join("/");
+    oracle::agree(
+        multitrack::inputs(machine, max_length),
+        |input, limit| Outcome::of(machine, input, limit, tape),
+        |input, limit| Outcome::of(minimized, input, limit, tape),
+        |_, _, _| {},
+    )
+}
@@ -0,0 +1,631 @@
+use crate::definition;
+use crate::engine::Status;
+use crate::multitrack;
+use crate::normalize;
+use crate::oracle::{self, Outcome, Verdict};
+use crate::rules::{self, RuleIndex};
+use crate::tape::{Symbol, Symbols, Tape, TapeMode};
+use crate::{KeyStates, Machine, TransitionFunction, LHS, RHS};
+use std::fs;
+
+// k-tape machines are written like k-track machines, with 'tapes: k' instead
+// of 'tracks: k' and one move per tape (L, R or S for stay):
+//
+//   tapes: 2
+//   initial: copy
+//   δ(copy,a□)=(copy,aa,RR)
+//
+// The cell a rule reads holds the symbol under each head, so sets, wildcards
+// and rule precedence work as they do for tracks. Every tape is infinite in
+// both directions and the input starts out on the first one.
+//
+// single_tape() is the textbook construction of an equivalent single-tape
+// machine: the tapes are written one after another between delimiters,
+// '#w1#w2#...#', with the symbol under each head marked. Every step sweeps
+// right to read the marked symbols, then back left to write and move the
+// marks. A head that moves past the end of its tape marks the delimiter, and
+// the next sweep shifts the rest of the tape right to make room.
+
+pub struct MultiTape {
+    /// The rules, with the tapes as tracks; the moves in the rules are
+    /// placeholders.
+    pub machine: Machine,
+    /// The moves of each rule, one per tape.
+    pub moves: Vec<Vec<char>>,
+}
+
+impl MultiTape {
+    pub fn tapes(&self) -> usize {
+        self.machine.tracks
+    }
+
+    /// The symbols each tape can hold, with the blank and, on the first
+    /// tape, the input alphabet.
+    pub fn alphabets(&self) -> Vec<Vec<char>> {
+        let mut alphabets = rules::tape_alphabet(&self.machine);
+        let declared = self.machine.input_alphabet.iter().flatten();
+        for c in declared.chain(['□'].iter()) {
+            if !alphabets[0].contains(c) {
+                alphabets[0].push(*c);
+            }
+        }
+        alphabets
+    }
+
+    /// Every symbol the tapes can hold, the blank first.
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut symbols = vec!['□'];
+        for c in self.alphabets().concat() {
+            if !symbols.contains(&c) {
+                symbols.push(c);
+            }
+        }
+        symbols
+    }
+}
+
+/// Tells whether a line of a definition is a rule.
+fn is_rule(line: &str) -> bool {
+    !line.is_empty()
+        && !line.starts_with('#')
+        && !definition::is_set_declaration(line)
+        && line
+            .split_once(':')
+            .is_none_or(|(key, _)| key.contains('('))
+}
+
+pub fn parse_multitape(text: &str) -> Result<MultiTape, String> {
+    let mut tapes = 1;
+    let mut lines: Vec<String> = Vec::new();
+    let mut moves: Vec<(usize, Vec<char>)> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let number = number + 1;
+        let line = line.trim();
+        if let Some(value) = definition::property(line, "tapes") {
+            tapes = value
+                .parse()
+                .ok()
+                .filter(|&t| t > 0)
+                .ok_or(format!("line {}: invalid number of tapes", number))?;
+            lines.push(format!("tracks: {}", value));
+        } else if is_rule(line) {
+            // the moves are checked here, everything else by parse_machine
+            let rule = line.replace([' ', '\t'], "");
+            let Some((rest, directions)) = rule
+                .strip_suffix(')')
+                .and_then(|rule| rule.rsplit_once(','))
+            else {
+                return Err(format!(
+                    "line {}: '{}' is not a transition function",
+                    number, line
+                ));
+            };
+            moves.push((number, directions.to_uppercase().chars().collect()));
+            lines.push(format!("{},R)", rest));
+        } else if definition::property(line, "tracks").is_some() {
+            return Err(format!(
+                "line {}: use 'tapes:' for a k-tape machine",
+                number
+            ));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    let machine = definition::parse_machine(&lines.join("\n"))?;
+    for (number, directions) in &moves {
+        if directions.len() != tapes || directions.iter().any(|c| !"LRS".contains(*c)) {
+            return Err(format!(
+                "line {}: expected {} move(s), each L, R or S",
+                number, tapes
+            ));
+        }
+    }
+    Ok(MultiTape {
+        machine,
+        moves: moves
+            .into_iter()
+            .map(|(_, directions)| directions)
+            .collect(),
+    })
+}
+
+pub fn read_multitape(path: &str) -> Result<MultiTape, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    parse_multitape(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Writes a k-tape machine in the definition file format.
+pub fn write_multitape(machine: &MultiTape) -> String {
+    let header = Machine {
+        transitions: Vec::new(),
+        ..machine.machine.clone()
+    };
+    let mut text = definition::write_machine(&header).replacen("tracks:", "tapes:", 1);
+    for (function, moves) in machine.machine.transitions.iter().zip(&machine.moves) {
+        text.push_str(&format!(
+            "δ({},{})=({},{},{})\n",
+            function.lhs.state,
+            function.lhs.input,
+            function.rhs.state,
+            function.rhs.replacement,
+            moves.iter().collect::<String>()
+        ));
+    }
+    text
+}
+
+/// One execution of a k-tape machine.
+pub struct MultiRun<'m> {
+    machine: &'m MultiTape,
+    index: RuleIndex,
+    symbols: Symbols,
+    tapes: Vec<Tape>,
+    heads: Vec<i64>,
+    pub state: String,
+    pub steps: usize,
+}
+
+impl<'m> MultiRun<'m> {
+    pub fn new(machine: &'m MultiTape, input: &str) -> MultiRun<'m> {
+        let mut symbols = Symbols::new(1);
+        let cells: Vec<Symbol> = input
+            .chars()
+            .map(|c| symbols.intern(&c.to_string()))
+            .collect();
+        let mut tapes = vec![Tape::new(&cells, TapeMode::TwoWay)];
+        tapes.resize_with(machine.tapes(), || Tape::new(&[], TapeMode::TwoWay));
+        MultiRun {
+            machine,
+            index: RuleIndex::new(&machine.machine),
+            symbols,
+            tapes,
+            heads: vec![0; machine.tapes()],
+            state: machine.machine.states.initial_state.clone(),
+            steps: 0,
+        }
+    }
+
+    /// Runs until the machine halts or `limit` steps have been taken.
+    pub fn run(&mut self, limit: usize) -> Status {
+        while self.steps < limit {
+            let cell: String = self
+                .tapes
+                .iter()
+                .zip(&self.heads)
+                .map(|(tape, &head)| self.symbols.name(tape.read(head)))
+                .collect();
+            let Some((rule, written)) = self.index.fire(&self.state, &cell) else {
+                let finals = &self.machine.machine.states.final_states;
+                return if finals.contains(&self.state) {
+                    Status::Accepted
+                } else {
+                    Status::Rejected
+                };
+            };
+            for (tape, c) in written.chars().enumerate() {
+                let symbol = self.symbols.intern(&c.to_string());
+                self.tapes[tape].write(self.heads[tape], symbol);
+                match self.machine.moves[rule][tape] {
+                    'L' => self.heads[tape] -= 1,
+                    'R' => self.heads[tape] += 1,
+                    _ => {}
+                }
+            }
+            self.state = self.machine.machine.transitions[rule].rhs.state.clone();
+            self.steps += 1;
+        }
+        Status::Running
+    }
+
+    /// The written part of each tape.
+    pub fn output(&self) -> Vec<String> {
+        self.tapes
+            .iter()
+            .map(|tape| {
+                let used: Vec<i64> = tape
+                    .span()
+                    .filter(|&position| tape.read(position) != Symbols::BLANK)
+                    .collect();
+                match (used.first(), used.last()) {
+                    (Some(&first), Some(&last)) => (first..=last)
+                        .map(|position| self.symbols.name(tape.read(position)))
+                        .collect(),
+                    _ => String::new(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The symbols the single-tape machine uses besides the k-tape alphabet.
+pub struct Encoding {
+    pub delimiter: char,
+    /// Stands for a blank between the delimiters, so that the blank only
+    /// ever marks the ends of the used tape.
+    pub space: char,
+    /// Each symbol with its marked version.
+    pub marks: Vec<(char, char)>,
+    /// A delimiter a head has moved onto from the left, from the right, or
+    /// from both sides; the next sweep makes room for the head.
+    pub overflows: [char; 3],
+}
+
+impl Encoding {
+    fn mark(&self, symbol: char) -> char {
+        self.marks
+            .iter()
+            .find(|(c, _)| *c == symbol)
+            .map_or(symbol, |(_, marked)| *marked)
+    }
+
+    /// A symbol as written between the delimiters.
+    fn plain(&self, symbol: char) -> char {
+        if symbol == '□' {
+            self.space
+        } else {
+            symbol
+        }
+    }
+
+    fn unmark(&self, symbol: char) -> char {
+        if symbol == self.space {
+            return '□';
+        }
+        self.marks
+            .iter()
+            .find(|(_, marked)| *marked == symbol)
+            .map_or(symbol, |(c, _)| *c)
+    }
+
+    /// The k tapes held by the single tape, trimmed like `MultiRun::output`.
+    pub fn decode(&self, tape: &str) -> Vec<String> {
+        let mut segments: Vec<String> = tape
+            .split(self.delimiter)
+            .map(|segment| {
+                let symbols: String = segment.chars().map(|c| self.unmark(c)).collect();
+                symbols.trim_matches('□').to_string()
+            })
+            .collect();
+        // the parts before the first and after the last delimiter
+        segments.pop();
+        segments.remove(0);
+        segments
+    }
+
+    /// Comment lines saying what the new symbols stand for.
+    pub fn comment(&self) -> String {
+        let [left, right, both] = self.overflows;
+        let mut text = format!(
+            "# {} separates the tapes, {} is a blank between them\n",
+            self.delimiter, self.space
+        );
+        for (symbol, marked) in &self.marks {
+            text.push_str(&format!("# {} = {} under a head\n", marked, symbol));
+        }
+        text.push_str(&format!(
+            "# {}, {}, {} = {} with a head moved onto it from the left, the right, both sides\n",
+            left, right, both, self.delimiter
+        ));
+        text
+    }
+}
+
+/// Builds the single-tape machine as it adds rules.
+struct Builder {
+    transitions: Vec<TransitionFunction>,
+}
+
+impl Builder {
+    fn add(&mut self, state: &str, read: char, next: &str, write: char, direction: char) {
+        self.transitions.push(TransitionFunction {
+            lhs: LHS {
+                state: state.to_string(),
+                input: read.to_string(),
+            },
+            rhs: RHS {
+                state: next.to_string(),
+                replacement: write.to_string(),
+                direction,
+            },
+        });
+    }
+}
+
+/// Converts a k-tape machine into a single-tape one.
+pub fn single_tape(machine: &MultiTape) -> Result<(Machine, Encoding), String> {
+    let tapes = machine.tapes();
+    let alphabets = machine.alphabets();
+    let alphabet = machine.alphabet();
+    let index = RuleIndex::new(&machine.machine);
+    let mut fresh = multitrack::fresh_symbols(alphabet.clone());
+    let mut take = || fresh.next().ok_or("not enough fresh symbols".to_string());
+    let delimiter = if alphabet.contains(&'#') {
+        take()?
+    } else {
+        '#'
+    };
+    let space = take()?;
+    let mut marks = Vec::new();
+    for &c in &alphabet {
+        marks.push((c, take()?));
+    }
+    let overflows = [take()?, take()?, take()?];
+    let encoding = Encoding {
+        delimiter,
+        space,
+        marks,
+        overflows,
+    };
+    let [from_left, from_right, from_both] = overflows;
+
+    // symbols between the delimiters, the delimiters, and marked symbols
+    let plain: Vec<char> = alphabet.iter().map(|&c| encoding.plain(c)).collect();
+    let delimiters: Vec<char> = [delimiter].into_iter().chain(overflows).collect();
+    let marked: Vec<char> = encoding.marks.iter().map(|(_, m)| *m).collect();
+    let unmarked: Vec<char> = plain.iter().chain(&delimiters).copied().collect();
+    let every: Vec<char> = unmarked.iter().chain(&marked).copied().collect();
+
+    let states = normalize::all_states(&machine.machine);
+    let home = |state: &str| format!("{}/home", state);
+    let scan = |state: &str, read: &str| format!("{}/{}", state, read);
+
+    let mut builder = Builder {
+        transitions: Vec::new(),
+    };
+    let b = &mut builder;
+
+    // write '#', mark the first input symbol and add an empty marked tape
+    // for each of the others
+    for &c in &alphabet {
+        b.add("init/mark", c, "init/left", encoding.mark(c), 'L');
+    }
+    b.add("init/left", '□', "init/input", delimiter, 'R');
+    for &c in plain.iter().chain(&marked) {
+        b.add("init/input", c, "init/input", c, 'R');
+    }
+    let initial = &machine.machine.states.initial_state;
+    if tapes == 1 {
+        b.add("init/input", '□', &home(initial), delimiter, 'L');
+    } else {
+        b.add("init/input", '□', "init/2", delimiter, 'R');
+    }
+    for tape in 2..=tapes {
+        let closed = format!("init/{}#", tape);
+        b.add(
+            &format!("init/{}", tape),
+            '□',
+            &closed,
+            encoding.mark('□'),
+            'R',
+        );
+        if tape == tapes {
+            b.add(&closed, '□', &home(initial), delimiter, 'L');
+        } else {
+            b.add(&closed, '□', &format!("init/{}", tape + 1), delimiter, 'R');
+        }
+    }
+
+    let mut final_states: Vec<String> = Vec::new();
+    for state in &states {
+        // back to the left end, then right collecting the marked symbols
+        for &c in &every {
+            b.add(&home(state), c, &home(state), c, 'L');
+        }
+        b.add(&home(state), '□', &scan(state, ""), '□', 'R');
+
+        // a head on a delimiter gets a blank cell of its own: the rest of the
+        // tape is carried one cell right and the sweep starts over
+        let shift = |c: char| format!("{}/>{}", state, c);
+        let mut reads = vec![String::new()];
+        for symbols in &alphabets {
+            let mut longer = Vec::new();
+            for read in &reads {
+                let current = scan(state, read);
+                for &c in plain.iter().chain([&delimiter]) {
+                    b.add(&current, c, &current, c, 'R');
+                }
+                b.add(
+                    &current,
+                    from_left,
+                    &shift(delimiter),
+                    encoding.mark('□'),
+                    'R',
+                );
+                b.add(
+                    &current,
+                    from_right,
+                    &shift(encoding.mark('□')),
+                    delimiter,
+                    'R',
+                );
+                b.add(
+                    &current,
+                    from_both,
+                    &shift(from_right),
+                    encoding.mark('□'),
+                    'R',
+                );
+                for (c, m) in encoding.marks.iter().filter(|(c, _)| symbols.contains(c)) {
+                    let next = format!("{}{}", read, c);
+                    b.add(&current, *m, &scan(state, &next), *m, 'R');
+                    longer.push(next);
+                }
+            }
+            reads = longer;
+        }
+        for &carried in &every {
+            for &c in &every {
+                b.add(&shift(carried), c, &shift(c), carried, 'R');
+            }
+            b.add(&shift(carried), '□', &home(state), carried, 'L');
+        }
+
+        for read in &reads {
+            let at_end = scan(state, read);
+            for &c in &plain {
+                b.add(&at_end, c, &at_end, c, 'R');
+            }
+            let Some((rule, written)) = index.fire(state, read) else {
+                if machine.machine.states.final_states.contains(state) {
+                    let halt = format!("{}/halt", state);
+                    b.add(&at_end, delimiter, &halt, delimiter, 'L');
+                    if !final_states.contains(&halt) {
+                        final_states.push(halt);
+                    }
+                }
+                continue;
+            };
+            let next_state = &machine.machine.transitions[rule].rhs.state;
+            let update = |tape: usize| format!("{}/{}/{}", state, read, tape);
+            b.add(&at_end, delimiter, &update(tapes), delimiter, 'L');
+
+            // the last tape first, each time leaving the head left of its mark
+            let read: Vec<char> = read.chars().collect();
+            let written: Vec<char> = written.chars().collect();
+            for tape in (1..=tapes).rev() {
+                let current = update(tape);
+                let done = if tape > 1 {
+                    update(tape - 1)
+                } else {
+                    home(next_state)
+                };
+                for &c in &unmarked {
+                    b.add(&current, c, &current, c, 'L');
+                }
+                let (symbol, write) = (encoding.mark(read[tape - 1]), written[tape - 1]);
+                let direction = machine.moves[rule][tape - 1];
+                if direction == 'S' {
+                    b.add(&current, symbol, &done, encoding.mark(write), 'L');
+                    continue;
+                }
+                let moved = format!("{}{}", current, direction);
+                b.add(&current, symbol, &moved, encoding.plain(write), direction);
+                for &c in &plain {
+                    let symbol = encoding.mark(encoding.unmark(c));
+                    b.add(&moved, c, &done, symbol, 'L');
+                }
+                // moving off the tape: the left delimiter hasn't been
+                // reached yet, the right one may already hold the next head
+                if direction == 'L' {
+                    b.add(&moved, delimiter, &done, from_right, 'L');
+                } else {
+                    b.add(&moved, delimiter, &done, from_left, 'L');
+                    b.add(&moved, from_right, &done, from_both, 'L');
+                }
+            }
+        }
+    }
+
+    let mut tape_alphabet = vec!['□'];
+    tape_alphabet.extend(every.iter().filter(|&&c| c != '□'));
+    let converted = Machine {
+        transitions: builder.transitions,
+        states: KeyStates {
+            initial_state: String::from("init/mark"),
+            final_states,
+        },
+        tracks: 1,
+        tape_mode: TapeMode::TwoWay,
+        sets: Vec::new(),
+        input_alphabet: machine.machine.input_alphabet.clone(),
+        tape_alphabet: Some(tape_alphabet),
+    };
+    Ok((converted, encoding))
+}
+
+/// Steps taken on the inputs of one length.
+pub struct Overhead {
+    pub length: usize,
+    pub inputs: usize,
+    /// The most steps the k-tape machine took.
+    pub steps: usize,
+    /// The most steps the single-tape machine took.
+    pub simulated: usize,
+}
+
+/// Runs both machines on every input up to `max_length` symbols with
+/// `oracle::agree`, and reports the steps each took per input length.
+pub fn overhead(
+    machine: &MultiTape,
+    converted: &Machine,
+    encoding: &Encoding,
+    max_length: usize,
+) -> Result<Vec<Overhead>, String> {
+    let alphabet: Vec<char> = match &machine.machine.input_alphabet {
+        Some(sigma) => sigma.clone(),
+        None => machine.alphabets()[0]
+            .iter()
+            .copied()
+            .filter(|&c| c != '□')
+            .collect(),
+    };
+    let mut report: Vec<Overhead> = (0..=max_length)
+        .map(|length| Overhead {
+            length,
+            inputs: 0,
+            steps: 0,
+            simulated: 0,
+        })
+        .collect();
+    oracle::agree(
+        oracle::inputs(&alphabet, max_length),
+        |input, limit| {
+            let mut original = MultiRun::new(machine, input);
+            Outcome {
+                verdict: Verdict::of(original.run(limit)),
+                tape: original.output().join(" | "),
+                steps: original.steps,
+            }
+        },
+        |input, limit| {
+            Outcome::of(converted, input, limit, |tapes| {
+                encoding.decode(&tapes[0]).join(" | ")
+            })
+        },
+        |input, original, single| {
+            let row = &mut report[input.chars().count()];
+            row.inputs += 1;
+            row.steps = row.steps.max(original.steps);
+            row.simulated = row.simulated.max(single.steps);
+        },
+    )?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multitape_definitions_are_parsed_and_run() {
+        let machine = parse_multitape(include_str!("../example.mtm")).unwrap();
+        assert_eq!(machine.tapes(), 2);
+        assert_eq!(machine.moves.len(), machine.machine.transitions.len());
+        assert_eq!(machine.moves[2], ['L', 'S']);
+        assert_eq!(MultiRun::new(&machine, "abba").run(1000), Status::Accepted);
+        assert_eq!(MultiRun::new(&machine, "abb").run(1000), Status::Rejected);
+    }
+
+    #[test]
+    fn bad_moves_are_refused() {
+        let error = |text: &str| parse_multitape(text).err().unwrap();
+        let header = "tapes: 2\ninitial: q0\n";
+        assert_eq!(
+            error("tracks: 2"),
+            "line 1: use 'tapes:' for a k-tape machine"
+        );
+        assert_eq!(error("tapes: none"), "line 1: invalid number of tapes");
+        assert_eq!(
+            error(&format!("{}δ(q0,aa)=(q0,aa,R)", header)),
+            "line 3: expected 2 move(s), each L, R or S"
+        );
+        assert_eq!(
+            error(&format!("{}δ(q0,aa)=(q0,aa,RX)", header)),
+            "line 3: expected 2 move(s), each L, R or S"
+        );
+        assert_eq!(
+            error(&format!("{}δ(q0,aa)=(q0,aa,RS", header)),
+            "line 3: 'δ(q0,aa)=(q0,aa,RS' is not a transition function"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::oracle::{self, Outcome};
+use crate::rules::{self, RuleIndex};
+use crate::{combine_tracks, KeyStates, Machine, TransitionFunction, LHS, RHS};
+
+// The classic construction of a single-track machine from a multi-track one:
+// every cell, a tuple with one symbol per track, becomes one symbol of the new
+// machine, and every state gets a rule for every tuple that some rule of the
+// original machine applies to. Cells hold one character, so each tuple is
+// written as a fresh symbol and the legend says which tuple it stands for.
+
+/// Characters used for tuples, in order of preference.
+const POOL: [(char, char); 5] = [('A', 'Z'), ('a', 'z'), ('0', '9'), ('À', 'ÿ'), ('Ā', 'ž')];
+
+/// Symbols from the pool that aren't in `reserved`, for constructions that
+/// need symbols of their own.
+pub fn fresh_symbols(reserved: Vec<char>) -> impl Iterator<Item = char> {
+    POOL.iter()
+        .flat_map(|&(first, last)| first..=last)
+        .filter(move |c| c.is_alphanumeric() && !reserved.contains(c))
+}
+
+/// A fresh symbol for each tuple, as a cell string of the original machine.
+pub struct Legend {
+    pub symbols: Vec<(char, String)>,
+}
+
+impl Legend {
+    fn symbol(&self, cell: &str) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|(_, c)| c == cell)
+            .map(|(symbol, _)| *symbol)
+    }
+
+    fn cell(&self, symbol: char) -> Option<&str> {
+        self.symbols
+            .iter()
+            .find(|(s, _)| *s == symbol)
+            .map(|(_, cell)| cell.as_str())
+    }
+
+    /// Encodes multi-track cells, given as one string, as single symbols.
+    pub fn encode(&self, input: &str, tracks: usize) -> Option<String> {
+        input
+            .chars()
+            .collect::<Vec<char>>()
+            .chunks(tracks)
+            .map(|chunk| self.symbol(&chunk.iter().collect::<String>()))
+            .collect()
+    }
+
+    /// One comment line per tuple, to put at the top of a definition.
+    pub fn comment(&self) -> String {
+        self.symbols
+            .iter()
+            .filter(|(symbol, _)| *symbol != '□')
+            .map(|(symbol, cell)| {
+                let tuple: Vec<String> = cell.chars().map(|c| c.to_string()).collect();
+                format!("# {} = ({})\n", symbol, tuple.join(", "))
+            })
+            .collect()
+    }
+}
+
+/// Every cell an input can hold: Σ on each track, or if Σ isn't declared
+/// every symbol but the blank that the rules use.
+pub fn input_cells(machine: &Machine) -> Vec<String> {
+    let sigma = match &machine.input_alphabet {
+        Some(sigma) => sigma.clone(),
+        None => {
+            let mut symbols: Vec<char> = rules::tape_alphabet(machine).concat();
+            symbols.retain(|&c| c != '□');
+            symbols.sort();
+            symbols.dedup();
+            symbols
+        }
+    };
+    rules::cells(&vec![sigma; machine.tracks])
+}
+
+/// Every input of at most `max_length` cells, shortest first.
+pub fn inputs(machine: &Machine, max_length: usize) -> impl Iterator<Item = String> {
+    let cells = input_cells(machine);
+    oracle::words(cells.len(), max_length)
+        .map(move |word| word.iter().map(|&i| cells[i].as_str()).collect())
+}
+
+fn states(machine: &Machine) -> Vec<String> {
+    let mut states: Vec<String> = Vec::new();
+    for function in &machine.transitions {
+        if !states.contains(&function.lhs.state) {
+            states.push(function.lhs.state.clone());
+        }
+    }
+    states
+}
+
+/// Converts a machine to a single-track one. With `reachable`, only tuples
+/// that can actually end up on the tape get a symbol, instead of every tuple
+/// over the tracks' alphabets.
+pub fn single_track(machine: &Machine, reachable: bool) -> Result<(Machine, Legend), String> {
+    let index = RuleIndex::new(machine);
+    let states = states(machine);
+    let blank = "□".repeat(machine.tracks);
+    let inputs = input_cells(machine);
+
+    let mut cells: Vec<String> = vec![blank.clone()];
+    let add = |cells: &mut Vec<String>, cell: &String| {
+        if !cells.contains(cell) {
+            cells.push(cell.clone());
+        }
+    };
+    inputs.iter().for_each(|cell| add(&mut cells, cell));
+    if !reachable {
+        let mut alphabet = rules::tape_alphabet(machine);
+        for track in alphabet.iter_mut() {
+            for cell in &inputs {
+                for c in cell.chars() {
+                    if !track.contains(&c) {
+                        track.push(c);
+                    }
+                }
+            }
+        }
+        rules::cells(&alphabet)
+            .iter()
+            .for_each(|cell| add(&mut cells, cell));
+    }
+
+    // rules for every state and tuple, adding tuples the rules write as they
+    // turn up
+    let mut transitions: Vec<(String, String, usize, String)> = Vec::new();
+    let mut next = 0;
+    while next < cells.len() {
+        let cell = cells[next].clone();
+        for state in &states {
+            if let Some((rule, written)) = index.fire(state, &cell) {
+                add(&mut cells, &written);
+                transitions.push((state.clone(), cell.clone(), rule, written));
+            }
+        }
+        next += 1;
+    }
+
+    let mut pool = fresh_symbols(rules::tape_alphabet(machine).concat());
+    let mut legend = Legend {
+        symbols: Vec::new(),
+    };
+    for cell in &cells {
+        let symbol = if *cell == blank {
+            '□'
+        } else {
+            pool.next().ok_or(format!(
+                "too many tuples ({}) to give each a symbol",
+                cells.len()
+            ))?
+        };
+        legend.symbols.push((symbol, cell.clone()));
+    }
+    let symbol = |cell: &str| legend.symbol(cell).unwrap().to_string();
+
+    let converted = Machine {
+        transitions: transitions
+            .iter()
+            .map(|(state, cell, rule, written)| {
+                let rhs = &machine.transitions[*rule].rhs;
+                TransitionFunction {
+                    lhs: LHS {
+                        state: state.clone(),
+                        input: symbol(cell),
+                    },
+                    rhs: RHS {
+                        state: rhs.state.clone(),
+                        replacement: symbol(written),
+                        direction: rhs.direction,
+                    },
+                }
+            })
+            .collect(),
+        states: KeyStates {
+            initial_state: machine.states.initial_state.clone(),
+            final_states: machine.states.final_states.clone(),
+        },
+        tracks: 1,
+        tape_mode: machine.tape_mode,
+        sets: Vec::new(),
+        input_alphabet: machine.input_alphabet.as_ref().map(|_| {
+            inputs
+                .iter()
+                .map(|cell| legend.symbol(cell).unwrap())
+                .collect()
+        }),
+        tape_alphabet: Some(legend.symbols.iter().map(|(symbol, _)| *symbol).collect()),
+    };
+    Ok((converted, legend))
+}
+
+/// Runs both machines on every input up to `max_length` cells with
+/// `oracle::agree`, and returns how many inputs were checked.
+pub fn verify(
+    machine: &Machine,
+    converted: &Machine,
+    legend: &Legend,
+    max_length: usize,
+) -> Result<usize, String> {
+    oracle::agree(
+        inputs(machine, max_length),
+        |input, limit| Outcome::of(machine, input, limit, |tracks| combine_tracks(&tracks)),
+        |input, limit| {
+            let encoded = legend.encode(input, machine.tracks).unwrap_or_default();
+            Outcome::of(converted, &encoded, limit, |tracks| {
+                tracks[0]
+                    .chars()
+                    .map(|c| legend.cell(c).unwrap_or("?"))
+                    .collect()
+            })
+        },
+        |_, _, _| {},
+    )
+}
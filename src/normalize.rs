@@ -0,0 +1,138 @@
+use crate::multitrack;
+use crate::rules::{self, RuleIndex};
+use crate::Machine;
+use std::collections::{HashMap, VecDeque};
+
+// The canonical form of a machine: states named q0, q1, ... in the order a
+// breadth-first walk from the initial state meets them, and the rules sorted
+// by state and then symbol. Symbols are taken in sorted order, blank first,
+// so two versions of a machine that differ only in names and rule order come
+// out the same.
+
+/// Every cell a machine can read, each track's symbols in sorted order with
+/// the blank first, so that the order doesn't depend on how the rules are
+/// written.
+pub fn cells(machine: &Machine) -> Vec<String> {
+    let alphabet: Vec<Vec<char>> = rules::tape_alphabet(machine)
+        .into_iter()
+        .map(|mut symbols| {
+            symbols.sort_by_key(|&c| (c != '□', c));
+            symbols
+        })
+        .collect();
+    let mut cells = rules::cells(&alphabet);
+    for cell in multitrack::input_cells(machine) {
+        if !cells.contains(&cell) {
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+/// The states the initial state reaches, breadth-first and taking the cells
+/// in order, and whether each rule ever fires on the way.
+pub fn reachable(
+    machine: &Machine,
+    index: &RuleIndex,
+    cells: &[String],
+) -> (Vec<String>, Vec<bool>) {
+    let mut states = vec![machine.states.initial_state.clone()];
+    let mut fired = vec![false; machine.transitions.len()];
+    let mut queue = VecDeque::from([machine.states.initial_state.clone()]);
+    while let Some(state) = queue.pop_front() {
+        for cell in cells {
+            if let Some((rule, _)) = index.fire(&state, cell) {
+                fired[rule] = true;
+                let next = &machine.transitions[rule].rhs.state;
+                if !states.contains(next) {
+                    states.push(next.clone());
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+    }
+    (states, fired)
+}
+
+/// Every state of a machine: the initial state, then in the order the rules
+/// and the final states mention them.
+pub fn all_states(machine: &Machine) -> Vec<String> {
+    let mut states = vec![machine.states.initial_state.clone()];
+    for state in machine
+        .transitions
+        .iter()
+        .flat_map(|f| [&f.lhs.state, &f.rhs.state])
+        .chain(&machine.states.final_states)
+    {
+        if !states.contains(state) {
+            states.push(state.clone());
+        }
+    }
+    states
+}
+
+/// Every state in the order they are reached from the initial state,
+/// breadth-first, then the ones it can't reach.
+pub fn states(machine: &Machine) -> Vec<String> {
+    let index = RuleIndex::new(machine);
+    let (mut order, _) = reachable(machine, &index, &cells(machine));
+    for state in all_states(machine) {
+        if !order.contains(&state) {
+            order.push(state);
+        }
+    }
+    order
+}
+
+/// Renames the states q0, q1, ... in the order of `states`. Returns the
+/// renamed machine and each old name with its new one.
+pub fn rename(machine: &Machine) -> (Machine, Vec<(String, String)>) {
+    let order = states(machine);
+    let names: HashMap<&String, String> = order
+        .iter()
+        .enumerate()
+        .map(|(i, state)| (state, format!("q{}", i)))
+        .collect();
+
+    let mut renamed = machine.clone();
+    for function in &mut renamed.transitions {
+        function.lhs.state = names[&function.lhs.state].clone();
+        function.rhs.state = names[&function.rhs.state].clone();
+    }
+    renamed.states.initial_state = names[&machine.states.initial_state].clone();
+    renamed.states.final_states = order
+        .iter()
+        .filter(|state| machine.states.final_states.contains(state))
+        .map(|state| names[state].clone())
+        .collect();
+    let pairs = order
+        .iter()
+        .map(|state| (state.clone(), names[state].clone()))
+        .collect();
+    (renamed, pairs)
+}
+
+/// Renames the states and sorts the rules by state and then by symbol, rules
+/// for one cell before rules with sets or wildcards. Those keep their order,
+/// as it decides between them when they overlap.
+pub fn normalize(machine: &Machine) -> (Machine, Vec<(String, String)>) {
+    let (mut normalized, names) = rename(machine);
+    let cells = cells(&normalized);
+    let number = |state: &String| names.iter().position(|(_, new)| new == state);
+    normalized.transitions.sort_by_key(|function| {
+        let symbol = cells
+            .iter()
+            .position(|cell| *cell == function.lhs.input)
+            .unwrap_or(cells.len());
+        (number(&function.lhs.state), symbol)
+    });
+    (normalized, names)
+}
+
+/// The renaming as comment lines, e.g. '# q0 = start'.
+pub fn comment(names: &[(String, String)]) -> String {
+    names
+        .iter()
+        .map(|(old, new)| format!("# {} = {}\n", new, old))
+        .collect()
+}
@@ -0,0 +1,347 @@
+use crate::multitape::{MultiRun, MultiTape};
+use crate::normalize;
+use crate::oracle::{self, Outcome, Verdict};
+use crate::rules::{self, RuleIndex, SymbolSet};
+use crate::tape::TapeMode;
+use crate::{KeyStates, Machine, TransitionFunction, LHS, RHS};
+use std::collections::HashMap;
+
+// A nondeterministic machine has several rules with the same state and input.
+// It accepts if some sequence of choices leads to a final state, and rejects
+// if every sequence halts elsewhere.
+//
+// determinize() builds the textbook deterministic 3-tape machine for it. Tape
+// 1 keeps the input, tape 2 is the tape being simulated and tape 3 holds an
+// address, a sequence of choices (1 for the first rule, 2 for the second,
+// ...). Every round copies the input to tape 2 and follows the choices on
+// tape 3 from the start, then the address is counted up, shortest first, so
+// that every computation is tried breadth-first. Left of the address a flag
+// remembers whether some computation was still running when its address ran
+// out; if none was once every address of a length has been tried, the
+// machine rejects.
+
+/// The rules that apply to a state and cell: the one that fires and every
+/// other rule with the same input, each with the cell it leaves behind.
+pub fn choices(
+    machine: &Machine,
+    index: &RuleIndex,
+    state: &str,
+    cell: &str,
+) -> Vec<(usize, String)> {
+    let Some((fired, _)) = index.fire(state, cell) else {
+        return Vec::new();
+    };
+    let lhs = &machine.transitions[fired].lhs;
+    machine
+        .transitions
+        .iter()
+        .enumerate()
+        .filter(|(_, function)| function.lhs == *lhs)
+        .filter_map(|(rule, function)| {
+            rules::apply(function, machine, cell).map(|written| (rule, written))
+        })
+        .collect()
+}
+
+/// The most rules that apply to one state and cell.
+pub fn branching(machine: &Machine) -> usize {
+    let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+    for function in &machine.transitions {
+        *counts
+            .entry((&function.lhs.state, &function.lhs.input))
+            .or_default() += 1;
+    }
+    counts.into_values().max().unwrap_or(0)
+}
+
+#[derive(Clone)]
+struct Configuration {
+    state: String,
+    tape: HashMap<i64, char>,
+    head: i64,
+}
+
+/// Runs every computation of a single-track machine breadth-first, on a
+/// two-way tape whatever the machine's own tape mode. Returns the verdict and
+/// the number of steps of the shortest accepting computation, or of the
+/// longest one when it rejects. Gives up with a timeout after `limit`
+/// configurations.
+pub fn run(machine: &Machine, input: &str, limit: usize) -> (Verdict, usize) {
+    let index = RuleIndex::new(machine);
+    let mut level = vec![Configuration {
+        state: machine.states.initial_state.clone(),
+        tape: input
+            .chars()
+            .enumerate()
+            .map(|(i, c)| (i as i64, c))
+            .collect(),
+        head: 0,
+    }];
+    let mut seen = 0;
+    let mut depth = 0;
+    while !level.is_empty() {
+        let mut next = Vec::new();
+        for configuration in level {
+            seen += 1;
+            if seen > limit {
+                return (Verdict::Timeout, depth);
+            }
+            let cell = configuration.tape.get(&configuration.head).unwrap_or(&'□');
+            let choices = choices(machine, &index, &configuration.state, &cell.to_string());
+            if choices.is_empty() && machine.states.final_states.contains(&configuration.state) {
+                return (Verdict::Accept, depth);
+            }
+            for (rule, written) in choices {
+                let function = &machine.transitions[rule];
+                let mut child = configuration.clone();
+                child
+                    .tape
+                    .insert(child.head, written.chars().next().unwrap_or('□'));
+                child.head += if function.rhs.direction == 'L' { -1 } else { 1 };
+                child.state = function.rhs.state.clone();
+                next.push(child);
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        level = next;
+        depth += 1;
+    }
+    (Verdict::Reject, depth)
+}
+
+/// Builds the rules of the 3-tape machine as it goes.
+struct Builder {
+    transitions: Vec<TransitionFunction>,
+    moves: Vec<Vec<char>>,
+}
+
+impl Builder {
+    fn add(&mut self, state: &str, read: &str, next: &str, write: &str, moves: &str) {
+        self.transitions.push(TransitionFunction {
+            lhs: LHS {
+                state: state.to_string(),
+                input: read.to_string(),
+            },
+            rhs: RHS {
+                state: next.to_string(),
+                replacement: write.to_string(),
+                direction: 'R',
+            },
+        });
+        self.moves.push(moves.chars().collect());
+    }
+}
+
+/// Tape 3 walks over the address and back while tape 2 writes and moves,
+/// which takes 2d steps for an address of length d: far enough to get past
+/// what the last round wrote, as it took at most d steps.
+fn walk(b: &mut Builder, state: &str, write: &str, moves: char, then: &str) {
+    let back = format!("{}/back", state);
+    let write = format!("?{}?", write);
+    b.add(state, "??[digit]", state, &write, &format!("S{}R", moves));
+    b.add(state, "??□", &back, "???", "SSL");
+    b.add(&back, "??[digit]", &back, &write, &format!("S{}L", moves));
+    b.add(&back, "??[flag]", then, "???", "SSR");
+}
+
+/// Builds the deterministic 3-tape machine for a single-track machine.
+pub fn determinize(machine: &Machine) -> Result<MultiTape, String> {
+    if machine.tracks != 1 {
+        return Err(String::from(
+            "only single-track machines can be determinized",
+        ));
+    }
+    if machine.tape_mode != TapeMode::TwoWay {
+        return Err(format!(
+            "the machine runs on a {} tape, determinizing is for the two-way tape",
+            machine.tape_mode.name()
+        ));
+    }
+    let choices_most = branching(machine);
+    if choices_most > 35 {
+        return Err(format!(
+            "{} rules for one state and symbol, at most 35 can be numbered",
+            choices_most
+        ));
+    }
+    let digits: Vec<char> = (1..=choices_most.max(1) as u32)
+        .map(|d| char::from_digit(d, 36).unwrap())
+        .collect();
+    let gamma = rules::tape_alphabet(machine).remove(0);
+    let index = RuleIndex::new(machine);
+
+    let states = normalize::all_states(machine);
+
+    let mut b = Builder {
+        transitions: Vec::new(),
+        moves: Vec::new(),
+    };
+    // the flag left of the address, then rounds
+    b.add("init", "???", "init/flag", "???", "SSL");
+    b.add("init/flag", "???", "clear", "??-", "SSR");
+
+    // clear tape 2 around the head, copy the input there and go back to its
+    // first cell
+    walk(&mut b, "clear", "?", 'L', "clear/blank");
+    walk(&mut b, "clear/blank", "□", 'R', "copy");
+    b.add("copy", "[x∈symbol]??", "copy", "?x?", "RRS");
+    b.add("copy", "□??", "copy/blank", "???", "SSS");
+    walk(&mut b, "copy/blank", "□", 'R', "return");
+    walk(&mut b, "return", "?", 'L', "return/input");
+    b.add("return/input", "???", "return/input2", "???", "LLS");
+    b.add("return/input2", "???", "return/input2", "???", "LLS");
+    let initial = format!("sim/{}", machine.states.initial_state);
+    b.add("return/input2", "□??", &initial, "???", "RRS");
+
+    // follow the address: a digit picks a rule, a digit without a rule ends
+    // the round, and the end of the address ends it with the computation
+    // still running
+    for state in &states {
+        let sim = format!("sim/{}", state);
+        let accepting = machine.states.final_states.contains(state);
+        for &c in &gamma {
+            let choices = choices(machine, &index, state, &c.to_string());
+            if choices.is_empty() {
+                let next = if accepting { "accept" } else { "dead" };
+                b.add(&sim, &format!("?{}?", c), next, "???", "SSS");
+                continue;
+            }
+            for (digit, (rule, written)) in digits.iter().zip(&choices) {
+                let function = &machine.transitions[*rule];
+                b.add(
+                    &sim,
+                    &format!("?{}{}", c, digit),
+                    &format!("sim/{}", function.rhs.state),
+                    &format!("?{}?", written),
+                    &format!("S{}R", function.rhs.direction),
+                );
+            }
+            if choices.len() < digits.len() {
+                b.add(&sim, &format!("?{}[digit]", c), "dead", "???", "SSS");
+            }
+            b.add(&sim, &format!("?{}□", c), "alive", "???", "SSS");
+        }
+    }
+
+    // count the address up: 1, 2, ..., 11, 12, ... and reject if every
+    // computation had halted before its address ran out
+    b.add("alive", "???", "alive", "???", "SSL");
+    b.add("alive", "??[flag]", "end", "??+", "SSR");
+    b.add("dead", "???", "dead", "???", "SSL");
+    b.add("dead", "??[flag]", "end", "???", "SSR");
+    b.add("end", "??[digit]", "end", "???", "SSR");
+    b.add("end", "??□", "count", "???", "SSL");
+    for pair in digits.windows(2) {
+        b.add(
+            "count",
+            &format!("??{}", pair[0]),
+            "rewind",
+            &format!("??{}", pair[1]),
+            "SSL",
+        );
+    }
+    let last = digits[digits.len() - 1];
+    let first = digits[0];
+    b.add(
+        "count",
+        &format!("??{}", last),
+        "count",
+        &format!("??{}", first),
+        "SSL",
+    );
+    b.add("count", "??+", "grow", "??-", "SSR");
+    b.add("count", "??-", "reject", "???", "SSS");
+    b.add("grow", "??[digit]", "grow", "???", "SSR");
+    b.add("grow", "??□", "rewind", &format!("??{}", first), "SSL");
+    b.add("rewind", "???", "rewind", "???", "SSL");
+    b.add("rewind", "??[flag]", "clear", "???", "SSR");
+
+    let symbols: Vec<char> = gamma.iter().copied().filter(|&c| c != '□').collect();
+    let deterministic = Machine {
+        transitions: b.transitions,
+        states: KeyStates {
+            initial_state: String::from("init"),
+            final_states: vec![String::from("accept")],
+        },
+        tracks: 3,
+        tape_mode: TapeMode::TwoWay,
+        sets: vec![
+            SymbolSet {
+                name: String::from("symbol"),
+                symbols,
+            },
+            SymbolSet {
+                name: String::from("digit"),
+                symbols: digits,
+            },
+            SymbolSet {
+                name: String::from("flag"),
+                symbols: vec!['-', '+'],
+            },
+        ],
+        input_alphabet: machine.input_alphabet.clone(),
+        tape_alphabet: None,
+    };
+    Ok(MultiTape {
+        machine: deterministic,
+        moves: b.moves,
+    })
+}
+
+/// Steps taken on the inputs of one length.
+pub struct Blowup {
+    pub length: usize,
+    pub inputs: usize,
+    /// The longest of the computations that decided an input.
+    pub depth: usize,
+    /// The most steps the deterministic machine took.
+    pub steps: usize,
+}
+
+/// Runs both machines on every input up to `max_length` symbols with
+/// `oracle::agree`, comparing only verdicts, and reports the steps taken per
+/// input length.
+pub fn verify(
+    machine: &Machine,
+    deterministic: &MultiTape,
+    max_length: usize,
+) -> Result<Vec<Blowup>, String> {
+    let alphabet = oracle::input_alphabet(machine);
+    let mut report: Vec<Blowup> = (0..=max_length)
+        .map(|length| Blowup {
+            length,
+            inputs: 0,
+            depth: 0,
+            steps: 0,
+        })
+        .collect();
+    oracle::agree(
+        oracle::inputs(&alphabet, max_length),
+        |input, limit| {
+            let (verdict, depth) = run(machine, input, limit);
+            // there is no single tape a nondeterministic machine leaves behind
+            Outcome {
+                verdict,
+                tape: String::new(),
+                steps: depth,
+            }
+        },
+        |input, limit| {
+            let mut simulation = MultiRun::new(deterministic, input);
+            Outcome {
+                verdict: Verdict::of(simulation.run(limit)),
+                tape: String::new(),
+                steps: simulation.steps,
+            }
+        },
+        |input, original, simulated| {
+            let row = &mut report[input.chars().count()];
+            row.inputs += 1;
+            row.depth = row.depth.max(original.steps);
+            row.steps = row.steps.max(simulated.steps);
+        },
+    )?;
+    Ok(report)
+}
@@ -0,0 +1,233 @@
+use crate::multitrack;
+use crate::normalize;
+use crate::oracle::{self, Outcome};
+use crate::rules::{self, RuleIndex};
+use crate::tape::TapeMode;
+use crate::{combine_tracks, KeyStates, Machine, TransitionFunction, LHS, RHS};
+
+// Converts a machine for the two-way infinite tape into one that runs on a
+// one-way tape. The input is first moved one cell right to make room for an
+// end marker. Whenever the machine moves onto the marker, where the two-way
+// tape would have had a fresh blank cell, the whole used tape is shifted one
+// cell right instead. Blanks the machine writes are written as a 'space' so
+// that an all-blank cell only ever follows the used tape, which is how the
+// shifting finds its end.
+
+/// The cells the one-way machine adds, with one character per track.
+pub struct Encoding {
+    pub marker: String,
+    pub space: String,
+}
+
+impl Encoding {
+    /// The cells of the two-way tape the one-way tape stands for, trimmed
+    /// like `Run::output`.
+    pub fn decode(&self, cells: &[String], blank: &str) -> Vec<String> {
+        let mut cells: Vec<&str> = cells
+            .iter()
+            .filter(|cell| **cell != self.marker)
+            .map(|cell| if *cell == self.space { blank } else { cell })
+            .collect();
+        while cells.last() == Some(&blank) {
+            cells.pop();
+        }
+        let first = cells.iter().take_while(|cell| **cell == blank).count();
+        cells.drain(..first);
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    pub fn comment(&self) -> String {
+        format!(
+            "# {} marks the left end, {} is a blank the machine has written\n",
+            self.marker, self.space
+        )
+    }
+}
+
+fn add(
+    transitions: &mut Vec<TransitionFunction>,
+    state: &str,
+    read: &str,
+    next: &str,
+    write: &str,
+    direction: char,
+) {
+    transitions.push(TransitionFunction {
+        lhs: LHS {
+            state: state.to_string(),
+            input: read.to_string(),
+        },
+        rhs: RHS {
+            state: next.to_string(),
+            replacement: write.to_string(),
+            direction,
+        },
+    });
+}
+
+pub fn one_way(machine: &Machine) -> Result<(Machine, Encoding), String> {
+    if machine.tape_mode.is_one_way() {
+        return Err(format!(
+            "the machine already runs on a {} tape",
+            machine.tape_mode.name()
+        ));
+    }
+    let index = RuleIndex::new(machine);
+    let reserved: Vec<char> = rules::tape_alphabet(machine).concat();
+    let mut fresh = multitrack::fresh_symbols(reserved.clone());
+    let marker = if reserved.contains(&'▷') {
+        fresh.next()
+    } else {
+        Some('▷')
+    }
+    .ok_or("no symbol left for the end marker")?;
+    let space = fresh.next().ok_or("no symbol left for a written blank")?;
+    let encoding = Encoding {
+        marker: marker.to_string().repeat(machine.tracks),
+        space: space.to_string().repeat(machine.tracks),
+    };
+    let blank = "□".repeat(machine.tracks);
+    let inputs = multitrack::input_cells(machine);
+
+    let states = normalize::all_states(machine);
+
+    // the cells that can be on the tape, and the rules of the original
+    // states, adding the cells the rules write as they turn up
+    let mut cells: Vec<String> = vec![blank.clone(), encoding.space.clone()];
+    cells.extend(inputs.iter().cloned());
+    let mut rules: Vec<(String, String, usize, String)> = Vec::new();
+    let mut next = 0;
+    while next < cells.len() {
+        let cell = cells[next].clone();
+        let read = if cell == encoding.space {
+            &blank
+        } else {
+            &cell
+        };
+        for state in &states {
+            if let Some((rule, written)) = index.fire(state, read) {
+                let written = if written == blank {
+                    encoding.space.clone()
+                } else {
+                    written
+                };
+                if !cells.contains(&written) {
+                    cells.push(written.clone());
+                }
+                rules.push((state.clone(), cell.clone(), rule, written));
+            }
+        }
+        next += 1;
+    }
+
+    let mut transitions: Vec<TransitionFunction> = Vec::new();
+    let t = &mut transitions;
+    let initial = &machine.states.initial_state;
+    let carry = |cell: &str| format!("init/>{}", cell);
+
+    // move the input one cell right and put the marker in front of it
+    add(t, "init/mark", &blank, initial, &encoding.marker, 'R');
+    for cell in &inputs {
+        add(t, "init/mark", cell, &carry(cell), &encoding.marker, 'R');
+        for next in &inputs {
+            add(t, &carry(cell), next, &carry(next), cell, 'R');
+        }
+        add(t, &carry(cell), &blank, "init/rewind", cell, 'L');
+        add(t, "init/rewind", cell, "init/rewind", cell, 'L');
+    }
+    add(
+        t,
+        "init/rewind",
+        &encoding.marker,
+        initial,
+        &encoding.marker,
+        'R',
+    );
+
+    for (state, cell, rule, written) in &rules {
+        let function = &machine.transitions[*rule];
+        add(
+            t,
+            state,
+            cell,
+            &function.rhs.state,
+            written,
+            function.rhs.direction,
+        );
+    }
+    for state in &states {
+        // a blank cell the original would find left of the tape: carry every
+        // cell one to the right, then come back to the new space
+        if index.fire(state, &blank).is_none() {
+            continue;
+        }
+        let shift = |cell: &str| format!("{}/>{}", state, cell);
+        let insert = format!("{}/insert", state);
+        let back = format!("{}/back", state);
+        add(t, state, &encoding.marker, &insert, &encoding.marker, 'R');
+        add(t, &insert, &blank, &back, &blank, 'L');
+        let used: Vec<&String> = cells.iter().filter(|cell| **cell != blank).collect();
+        for &cell in &used {
+            add(t, &insert, cell, &shift(cell), &encoding.space, 'R');
+            for &next in &used {
+                add(t, &shift(cell), next, &shift(next), cell, 'R');
+            }
+            add(t, &shift(cell), &blank, &back, cell, 'L');
+            add(t, &back, cell, &back, cell, 'L');
+        }
+        add(t, &back, &encoding.marker, state, &encoding.marker, 'R');
+    }
+
+    let mut tape_alphabet = vec!['□'];
+    for c in cells
+        .iter()
+        .chain([&encoding.marker])
+        .flat_map(|cell| cell.chars())
+    {
+        if !tape_alphabet.contains(&c) {
+            tape_alphabet.push(c);
+        }
+    }
+    let converted = Machine {
+        transitions,
+        states: KeyStates {
+            initial_state: String::from("init/mark"),
+            final_states: machine.states.final_states.clone(),
+        },
+        tracks: machine.tracks,
+        tape_mode: TapeMode::OneWayCrash,
+        sets: Vec::new(),
+        input_alphabet: machine.input_alphabet.clone(),
+        tape_alphabet: Some(tape_alphabet),
+    };
+    Ok((converted, encoding))
+}
+
+/// Runs both machines on every input up to `max_length` cells with
+/// `oracle::agree`, and returns how many inputs were checked.
+pub fn verify(
+    machine: &Machine,
+    converted: &Machine,
+    encoding: &Encoding,
+    max_length: usize,
+) -> Result<usize, String> {
+    let blank = "□".repeat(machine.tracks);
+    let cells = |tracks: Vec<String>| -> Vec<String> {
+        combine_tracks(&tracks)
+            .chars()
+            .collect::<Vec<char>>()
+            .chunks(machine.tracks)
+            .map(|chunk| chunk.iter().collect())
+            .collect()
+    };
+    oracle::agree(
+        multitrack::inputs(machine, max_length),
+        |input, limit| Outcome::of(machine, input, limit, |tracks| cells(tracks).concat()),
+        |input, limit| {
+            Outcome::of(converted, input, limit, |tracks| {
+                encoding.decode(&cells(tracks), &blank).concat()
+            })
+        },
+        |_, _, _| {},
+    )
+}
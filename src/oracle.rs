@@ -0,0 +1,319 @@
+use crate::engine::{Run, Status};
+use crate::rules;
+use crate::suite;
+use crate::Machine;
+use regex::Regex;
+
+// Checks a recognizer against a reference by running it on every input over
+// Σ up to some length. Inputs are enumerated shortest first, so the first
+// counterexamples found are also the shortest ones.
+
+/// Steps a run may take before it counts as not halting.
+pub const STEP_LIMIT: usize = 100_000;
+
+type Predicate = fn(&str) -> bool;
+
+/// Predicates that can be used as a reference by name.
+pub const PREDICATES: [(&str, &str, Predicate); 4] = [
+    ("anbn", "a(n)b(n), n ≥ 1", anbn),
+    ("anbncn", "a(n)b(n)c(n), n ≥ 1", anbncn),
+    ("palindrome", "reads the same backwards", palindrome),
+    ("ww", "the same half twice", ww),
+];
+
+fn anbn(input: &str) -> bool {
+    let n = input.len() / 2;
+    n >= 1 && input == "a".repeat(n) + &"b".repeat(n)
+}
+
+fn anbncn(input: &str) -> bool {
+    let n = input.len() / 3;
+    n >= 1 && input == "a".repeat(n) + &"b".repeat(n) + &"c".repeat(n)
+}
+
+fn palindrome(input: &str) -> bool {
+    input.chars().eq(input.chars().rev())
+}
+
+fn ww(input: &str) -> bool {
+    let chars: Vec<char> = input.chars().collect();
+    let (first, second) = chars.split_at(chars.len() / 2);
+    first == second
+}
+
+/// What a machine is checked against.
+pub enum Reference {
+    Regex(Regex),
+    Predicate(Predicate),
+    Machine(Machine),
+}
+
+impl Reference {
+    /// A built-in predicate by name, 'regex:<pattern>' for a regular
+    /// expression that must match the whole input, or else a machine.
+    pub fn parse(
+        text: &str,
+        load: impl Fn(&str) -> Result<Machine, String>,
+    ) -> Result<Reference, String> {
+        if let Some(pattern) = text.strip_prefix("regex:") {
+            return Regex::new(&format!("^(?:{})$", pattern))
+                .map(Reference::Regex)
+                .map_err(|e| e.to_string());
+        }
+        match PREDICATES.iter().find(|(name, _, _)| *name == text) {
+            Some((_, _, predicate)) => Ok(Reference::Predicate(*predicate)),
+            None => load(text).map(Reference::Machine),
+        }
+    }
+
+    fn verdict(&self, input: &str) -> Verdict {
+        match self {
+            Reference::Regex(regex) => Verdict::from(regex.is_match(input)),
+            Reference::Predicate(predicate) => Verdict::from(predicate(input)),
+            Reference::Machine(machine) => verdict(machine, input),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Verdict {
+    Accept,
+    Reject,
+    /// Still running after `STEP_LIMIT` steps.
+    Timeout,
+}
+
+impl Verdict {
+    fn from(accepted: bool) -> Verdict {
+        if accepted {
+            Verdict::Accept
+        } else {
+            Verdict::Reject
+        }
+    }
+
+    pub fn of(status: Status) -> Verdict {
+        match status {
+            Status::Accepted => Verdict::Accept,
+            Status::Rejected | Status::Crashed => Verdict::Reject,
+            Status::Running => Verdict::Timeout,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Accept => "accept",
+            Verdict::Reject => "reject",
+            Verdict::Timeout => "timeout",
+        }
+    }
+}
+
+pub fn verdict(machine: &Machine, input: &str) -> Verdict {
+    Verdict::of(Run::new(machine, input).run(STEP_LIMIT))
+}
+
+/// The input alphabet of a single-track machine: Σ if it is declared,
+/// otherwise every symbol but the blank that its rules use.
+pub fn input_alphabet(machine: &Machine) -> Vec<char> {
+    match &machine.input_alphabet {
+        Some(sigma) => sigma.clone(),
+        None => rules::tape_alphabet(machine)[0]
+            .iter()
+            .copied()
+            .filter(|&c| c != '□')
+            .collect(),
+    }
+}
+
+/// Every word over the letters 0..`letters` of at most `max_length`
+/// letters, shortest first and in order within one length.
+pub fn words(letters: usize, max_length: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut current: Option<Vec<usize>> = Some(Vec::new());
+    std::iter::from_fn(move || {
+        let word = current.take()?;
+        // count up in base `letters`, growing the word once every digit wraps
+        let mut next = word.clone();
+        let mut position = next.len();
+        loop {
+            if position == 0 {
+                if next.len() < max_length && letters > 0 {
+                    current = Some(vec![0; next.len() + 1]);
+                }
+                break;
+            }
+            position -= 1;
+            next[position] += 1;
+            if next[position] < letters {
+                current = Some(next);
+                break;
+            }
+            next[position] = 0;
+        }
+        Some(word)
+    })
+}
+
+/// Every string over `alphabet` of at most `max_length` symbols, shortest
+/// first and in alphabet order within one length.
+pub fn inputs(alphabet: &[char], max_length: usize) -> impl Iterator<Item = String> + '_ {
+    words(alphabet.len(), max_length).map(|word| word.iter().map(|&i| alphabet[i]).collect())
+}
+
+pub struct Counterexample {
+    pub input: String,
+    pub machine: Verdict,
+    pub reference: Verdict,
+}
+
+pub struct Report {
+    pub checked: usize,
+    pub counterexamples: Vec<Counterexample>,
+}
+
+/// Runs the machine and the reference on every input up to `max_length`.
+pub fn compare(
+    machine: &Machine,
+    reference: &Reference,
+    alphabet: &[char],
+    max_length: usize,
+) -> Report {
+    let mut report = Report {
+        checked: 0,
+        counterexamples: Vec::new(),
+    };
+    for input in inputs(alphabet, max_length) {
+        report.checked += 1;
+        let expected = reference.verdict(&input);
+        let actual = verdict(machine, &input);
+        if actual != expected {
+            report.counterexamples.push(Counterexample {
+                input,
+                machine: actual,
+                reference: expected,
+            });
+        }
+    }
+    report
+}
+
+/// How a machine ran on one input.
+pub struct Outcome {
+    pub verdict: Verdict,
+    /// The tape it left behind, written out the same way for both machines
+    /// being compared.
+    pub tape: String,
+    pub steps: usize,
+}
+
+impl Outcome {
+    /// Runs a machine for up to `limit` steps, writing out the tape it leaves
+    /// behind with `tape`.
+    pub fn of(
+        machine: &Machine,
+        input: &str,
+        limit: usize,
+        tape: impl FnOnce(Vec<String>) -> String,
+    ) -> Outcome {
+        let mut run = Run::new(machine, input);
+        let verdict = Verdict::of(run.run(limit));
+        Outcome {
+            verdict,
+            tape: tape(run.output()),
+            steps: run.steps,
+        }
+    }
+}
+
+/// Runs a machine and a construction from it on every input, and returns how
+/// many inputs were checked or the first one they disagree on, by verdict or
+/// by tape. Each runner is given the input and a step limit: `STEP_LIMIT` for
+/// the original, whose timeouts are left out, and `suite::STEP_LIMIT` for the
+/// construction, which may take far more steps. `checked` sees both outcomes
+/// of every input they agree on.
+pub fn agree(
+    inputs: impl IntoIterator<Item = String>,
+    mut original: impl FnMut(&str, usize) -> Outcome,
+    mut construction: impl FnMut(&str, usize) -> Outcome,
+    mut checked: impl FnMut(&str, &Outcome, &Outcome),
+) -> Result<usize, String> {
+    let mut count = 0;
+    for input in inputs {
+        let expected = original(&input, STEP_LIMIT);
+        if expected.verdict == Verdict::Timeout {
+            continue;
+        }
+        let actual = construction(&input, suite::STEP_LIMIT);
+        let shown = if input.is_empty() { "ε" } else { &input };
+        if actual.verdict != expected.verdict {
+            return Err(format!(
+                "the machines differ on input '{}': {} and {}",
+                shown,
+                expected.verdict.name(),
+                actual.verdict.name()
+            ));
+        }
+        if actual.tape != expected.tape {
+            return Err(format!(
+                "the machines leave different tapes on input '{}': {} and {}",
+                shown, expected.tape, actual.tape
+            ));
+        }
+        checked(&input, &expected, &actual);
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(verdict: Verdict, tape: &str) -> Outcome {
+        Outcome {
+            verdict,
+            tape: tape.to_string(),
+            steps: 0,
+        }
+    }
+
+    #[test]
+    fn inputs_come_shortest_first() {
+        let inputs: Vec<String> = inputs(&['a', 'b'], 2).collect();
+        assert_eq!(inputs, ["", "a", "b", "aa", "ab", "ba", "bb"]);
+    }
+
+    #[test]
+    fn agree_reports_the_first_difference() {
+        let inputs = || ["", "a", "b", "ab"].map(String::from);
+        let original = |input: &str, _| match input {
+            "a" => outcome(Verdict::Timeout, ""),
+            _ => outcome(Verdict::Accept, input),
+        };
+        let mut seen = Vec::new();
+        let checked = agree(inputs(), original, original, |input, _, _| {
+            seen.push(input.to_string())
+        });
+        // the timeout is left out
+        assert_eq!(checked, Ok(3));
+        assert_eq!(seen, ["", "b", "ab"]);
+
+        let rejects = |input: &str, _| match input {
+            "ab" => outcome(Verdict::Reject, input),
+            _ => outcome(Verdict::Accept, input),
+        };
+        assert_eq!(
+            agree(inputs(), original, rejects, |_, _, _| {}),
+            Err(String::from(
+                "the machines differ on input 'ab': accept and reject"
+            ))
+        );
+        let erases = |_: &str, _| outcome(Verdict::Accept, "");
+        assert_eq!(
+            agree(inputs(), original, erases, |_, _, _| {}),
+            Err(String::from(
+                "the machines leave different tapes on input 'b': b and "
+            ))
+        );
+    }
+}
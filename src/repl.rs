@@ -0,0 +1,264 @@
+use crate::check;
+use crate::definition::{self, format_function, format_set};
+use crate::rules;
+use crate::tape::TapeMode;
+use crate::{combine_tracks, function_validator, get_input, load_machine, run_input};
+use crate::{Machine, Options};
+use colored::*;
+use std::io::{self, Write};
+
+const HELP: &str = "\
+add <rule>                   add a rule, e.g. add δ(q0,a)=(q1,b,R)
+add final <q>                make q a final state
+add set <name> = {..}        declare a symbol set, e.g. add set digit = {0..9}
+remove <n>                   remove rule number n (see list)
+remove final <q>             make q a non-final state
+remove set <name>            remove a symbol set
+set initial <q>              make q the initial state
+set tracks <n>               change the number of tracks
+set tape <mode>              two-way, one-way-stay or one-way-crash
+set input_alphabet {..}      declare Σ (empty braces to remove it)
+set tape_alphabet {..}       declare Γ (empty braces to remove it)
+list                         show the machine
+check                        report problems in the machine
+run <track 1> [track 2 ...]  run the machine on an input
+save <file>                  write the machine to a definition (.tm) or CSV file
+load <file>                  replace the machine with one from a file
+undo                         undo the last change
+help                         show this help
+quit                         leave";
+
+/// An editing session over one machine.
+pub struct Repl<'o> {
+    pub machine: Machine,
+    history: Vec<Machine>,
+    options: &'o Options,
+}
+
+impl<'o> Repl<'o> {
+    pub fn new(machine: Machine, options: &'o Options) -> Repl<'o> {
+        Repl {
+            machine,
+            history: Vec::new(),
+            options,
+        }
+    }
+
+    /// Runs one command line. Returns false once the user quits.
+    pub fn command(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let before = self.machine.clone();
+        let result = match command {
+            "" => Ok(false),
+            "help" | "h" => {
+                println!("{}", HELP);
+                Ok(false)
+            }
+            "quit" | "q" | "exit" => return false,
+            "list" | "ls" => {
+                self.list();
+                Ok(false)
+            }
+            "check" => {
+                self.check();
+                Ok(false)
+            }
+            "run" => self.run(rest).map(|_| false),
+            "save" => self.save(rest).map(|_| false),
+            "load" => load_machine(rest, self.options)
+                .map(|machine| {
+                    self.machine = machine;
+                    true
+                })
+                .map_err(|e| e.to_string()),
+            "undo" => match self.history.pop() {
+                Some(machine) => {
+                    self.machine = machine;
+                    println!("Undone");
+                    return true;
+                }
+                None => Err(String::from("nothing to undo")),
+            },
+            "add" => self.add(rest),
+            "remove" | "rm" => self.remove(rest),
+            "set" => self.set(rest),
+            _ => Err(format!("unknown command '{}', type 'help'", command)),
+        };
+        match result {
+            Ok(true) => self.history.push(before),
+            Ok(false) => {}
+            Err(e) => println!("{}", format!("Error: {}", e).red()),
+        }
+        true
+    }
+
+    fn add(&mut self, rest: &str) -> Result<bool, String> {
+        if let Some(state) = rest.strip_prefix("final ") {
+            let state = state.trim().to_string();
+            if self.machine.states.final_states.contains(&state) {
+                return Err(format!("'{}' is already final", state));
+            }
+            self.machine.states.final_states.push(state);
+        } else if let Some(declaration) = rest.strip_prefix("set ") {
+            let set = rules::parse_set(declaration)?;
+            self.machine.sets.retain(|s| s.name != set.name);
+            self.machine.sets.push(set);
+        } else {
+            let text = rest.replace(' ', "");
+            if !function_validator(&text) {
+                return Err(String::from("invalid format, e.g. add δ(q0,a)=(q1,b,R)"));
+            }
+            let function = definition::parse_function(&text)?;
+            rules::check(&function, self.machine.tracks, &self.machine.sets)?;
+            println!(
+                "Added rule {}: {}",
+                self.machine.transitions.len() + 1,
+                format_function(&function)
+            );
+            self.machine.transitions.push(function);
+        }
+        Ok(true)
+    }
+
+    fn remove(&mut self, rest: &str) -> Result<bool, String> {
+        if let Some(state) = rest.strip_prefix("final ") {
+            let state = state.trim();
+            if !self.machine.states.final_states.iter().any(|s| s == state) {
+                return Err(format!("'{}' isn't a final state", state));
+            }
+            self.machine.states.final_states.retain(|s| s != state);
+        } else if let Some(name) = rest.strip_prefix("set ") {
+            let name = name.trim();
+            if !self.machine.sets.iter().any(|s| s.name == name) {
+                return Err(format!("there is no set '{}'", name));
+            }
+            self.machine.sets.retain(|s| s.name != name);
+        } else {
+            let number: usize = rest
+                .parse()
+                .map_err(|_| format!("'{}' is not a rule number", rest))?;
+            if number == 0 || number > self.machine.transitions.len() {
+                return Err(format!("there is no rule {}", number));
+            }
+            let function = self.machine.transitions.remove(number - 1);
+            println!("Removed rule {}: {}", number, format_function(&function));
+        }
+        Ok(true)
+    }
+
+    fn set(&mut self, rest: &str) -> Result<bool, String> {
+        let (property, value) = rest.split_once(' ').unwrap_or((rest, ""));
+        let value = value.trim();
+        match property {
+            "initial" if !value.is_empty() => self.machine.states.initial_state = value.to_string(),
+            "tracks" => {
+                let tracks: usize = value
+                    .parse()
+                    .ok()
+                    .filter(|&t| t > 0)
+                    .ok_or(format!("'{}' is not a number of tracks", value))?;
+                for function in &self.machine.transitions {
+                    rules::check(function, tracks, &self.machine.sets)
+                        .map_err(|e| format!("{}: {}", format_function(function), e))?;
+                }
+                self.machine.tracks = tracks;
+            }
+            "tape" => {
+                self.machine.tape_mode =
+                    TapeMode::from_name(value).ok_or(format!("unknown tape mode '{}'", value))?
+            }
+            "input_alphabet" | "Σ" => self.machine.input_alphabet = parse_alphabet(value)?,
+            "tape_alphabet" | "Γ" => {
+                self.machine.tape_alphabet = rules::with_blank(parse_alphabet(value)?)
+            }
+            _ => return Err(format!("can't set '{}', type 'help'", rest)),
+        }
+        Ok(true)
+    }
+
+    fn list(&self) {
+        let machine = &self.machine;
+        println!("tracks: {}", machine.tracks);
+        println!("tape: {}", machine.tape_mode.name());
+        println!("initial: {}", machine.states.initial_state);
+        println!("final: {}", machine.states.final_states.join(", "));
+        if let Some(sigma) = &machine.input_alphabet {
+            println!("input_alphabet: {}", rules::format_symbols(sigma));
+        }
+        if let Some(gamma) = &machine.tape_alphabet {
+            println!("tape_alphabet: {}", rules::format_symbols(gamma));
+        }
+        for set in &machine.sets {
+            println!("{}", format_set(set));
+        }
+        for (index, function) in machine.transitions.iter().enumerate() {
+            println!("{:>4}  {}", index + 1, format_function(function));
+        }
+    }
+
+    fn check(&self) {
+        let diagnostics = check::check(&self.machine);
+        if diagnostics.is_empty() {
+            println!("{}", "No problems found".green());
+        }
+        check::print(&diagnostics);
+    }
+
+    fn run(&self, rest: &str) -> Result<(), String> {
+        if self.machine.states.initial_state.is_empty() {
+            return Err(String::from("set an initial state first"));
+        }
+        let mut inputs: Vec<String> = rest
+            .split_whitespace()
+            .map(|s| s.replace("blank", "□"))
+            .collect();
+        if inputs.is_empty() {
+            inputs = vec![String::new(); self.machine.tracks];
+        }
+        if inputs.len() != self.machine.tracks {
+            return Err(format!(
+                "expected {} track(s), got {}",
+                self.machine.tracks,
+                inputs.len()
+            ));
+        }
+        if inputs
+            .iter()
+            .any(|s| s.chars().count() != inputs[0].chars().count())
+        {
+            return Err(String::from("tracks must have the same length"));
+        }
+        run_input(combine_tracks(&inputs), &self.machine, self.options);
+        Ok(())
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        if path.is_empty() {
+            return Err(String::from("save needs a file name"));
+        }
+        definition::save_machine(path, &self.machine).map_err(|e| e.to_string())?;
+        println!("Saved to {}", path);
+        Ok(())
+    }
+}
+
+fn parse_alphabet(value: &str) -> Result<Option<Vec<char>>, String> {
+    let symbols = rules::parse_symbols(value)?;
+    Ok((!symbols.is_empty()).then_some(symbols))
+}
+
+pub fn repl(machine: Machine, options: &Options) {
+    println!("Turing Machine Simulator");
+    println!("Type 'help' for a list of commands");
+    let mut repl = Repl::new(machine, options);
+    loop {
+        print!("> ");
+        io::stdout().flush().expect("failed to flush");
+        let line = get_input();
+        if line.is_empty() || !repl.command(&line) {
+            break;
+        }
+    }
+}
@@ -0,0 +1,582 @@
+use crate::{Machine, TransitionFunction};
+use std::collections::HashMap;
+
+/// Marks a track that matches any symbol when read and keeps the symbol
+/// that was read when written.
+pub const WILDCARD: char = '?';
+
+/// A named set of symbols, declared as e.g. `digit = {0..9}` or
+/// `marked = {X, Y}`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SymbolSet {
+    pub name: String,
+    pub symbols: Vec<char>,
+}
+
+/// What a rule expects on one track of the cell under the head.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TrackPattern {
+    Symbol(char),
+    /// Any symbol of a set, e.g. `[digit]`; `[d∈digit]` also binds the
+    /// symbol that was read to `d`.
+    Class {
+        binding: Option<String>,
+        symbols: Vec<char>,
+    },
+    Any,
+}
+
+impl TrackPattern {
+    pub fn matches(&self, symbol: char) -> bool {
+        match self {
+            TrackPattern::Symbol(c) => *c == symbol,
+            TrackPattern::Class { symbols, .. } => symbols.contains(&symbol),
+            TrackPattern::Any => true,
+        }
+    }
+}
+
+/// What a rule writes to one track of the cell under the head.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TrackWrite {
+    Symbol(char),
+    /// The symbol bound to a variable by a class in the rule's input.
+    Variable(String),
+    Same,
+}
+
+/// Parses a set declaration such as `digit = {0..9}`.
+pub fn parse_set(text: &str) -> Result<SymbolSet, String> {
+    let Some((name, body)) = text.split_once('=') else {
+        return Err(format!("'{}' is not a set declaration", text));
+    };
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("invalid set name '{}'", name));
+    }
+    Ok(SymbolSet {
+        name: name.to_string(),
+        symbols: parse_symbols(body.trim())?,
+    })
+}
+
+/// Parses `{a, b, 0..9}` into its symbols.
+pub fn parse_symbols(text: &str) -> Result<Vec<char>, String> {
+    let Some(inner) = text.strip_prefix('{').and_then(|t| t.strip_suffix('}')) else {
+        return Err(format!("set '{}' must be written in braces", text));
+    };
+    let mut symbols: Vec<char> = Vec::new();
+    if inner.trim().is_empty() {
+        return Ok(symbols);
+    }
+    for item in inner
+        .split(',')
+        .map(|item| item.trim().replace("blank", "□"))
+    {
+        let chars: Vec<char> = item.chars().collect();
+        let new_symbols: Vec<char> = match chars.as_slice() {
+            [c] => vec![*c],
+            [from, '.', '.', to] if from <= to => (*from..=*to).collect(),
+            _ => return Err(format!("invalid set item '{}'", item)),
+        };
+        for symbol in new_symbols {
+            if symbol == WILDCARD {
+                return Err(format!("'{}' can't be part of a set", WILDCARD));
+            }
+            if !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+pub fn format_symbols(symbols: &[char]) -> String {
+    let symbols: Vec<String> = symbols.iter().map(|c| c.to_string()).collect();
+    format!("{{{}}}", symbols.join(", "))
+}
+
+/// The blank is part of every tape alphabet, declared or not.
+pub fn with_blank(alphabet: Option<Vec<char>>) -> Option<Vec<char>> {
+    alphabet.map(|mut symbols| {
+        if !symbols.contains(&'□') {
+            symbols.push('□');
+        }
+        symbols
+    })
+}
+
+/// Splits a cell into one token per track: either a single character or a
+/// bracketed group such as `[d∈digit]`.
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '[' {
+            tokens.push(c.to_string());
+            continue;
+        }
+        let mut group = String::new();
+        let mut depth = 1;
+        for c in chars.by_ref() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+            group.push(c);
+        }
+        if depth != 0 {
+            return Err(format!("unclosed '[' in '{}'", text));
+        }
+        tokens.push(format!("[{}]", group));
+    }
+    Ok(tokens)
+}
+
+fn parse_class(group: &str, sets: &[SymbolSet]) -> Result<TrackPattern, String> {
+    let (binding, set) = match group.split_once('∈').or_else(|| group.split_once(':')) {
+        Some((binding, set)) => (Some(binding.trim().to_string()), set.trim()),
+        None => (None, group.trim()),
+    };
+    if binding.as_ref().is_some_and(|b| b.is_empty()) {
+        return Err(format!("missing variable name in '[{}]'", group));
+    }
+    let symbols = if set.starts_with('{') {
+        parse_symbols(set)?
+    } else {
+        match sets.iter().find(|s| s.name == set) {
+            Some(s) => s.symbols.clone(),
+            None => return Err(format!("unknown symbol set '{}'", set)),
+        }
+    };
+    Ok(TrackPattern::Class { binding, symbols })
+}
+
+/// Writes the named sets in a rule's input inline, e.g. `[d∈digit]` as
+/// `[d∈{0, 1, ...}]`, for formats that can't declare sets. Groups that
+/// aren't named sets are left as they are.
+pub fn inline_sets(text: &str, sets: &[SymbolSet]) -> String {
+    let Ok(tokens) = tokenize(text) else {
+        return text.to_string();
+    };
+    tokens
+        .into_iter()
+        .map(|token| {
+            let Some(group) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) else {
+                return token;
+            };
+            let (binding, name) = match group.split_once('∈').or_else(|| group.split_once(':')) {
+                Some((binding, name)) => (Some(binding.trim()), name.trim()),
+                None => (None, group.trim()),
+            };
+            match (sets.iter().find(|set| set.name == name), binding) {
+                (Some(set), Some(binding)) => {
+                    format!("[{}∈{}]", binding, format_symbols(&set.symbols))
+                }
+                (Some(set), None) => format!("[{}]", format_symbols(&set.symbols)),
+                (None, _) => token,
+            }
+        })
+        .collect()
+}
+
+/// Splits a rule's input into one pattern per track. A lone `?` stands for
+/// every track.
+pub fn parse_pattern(
+    text: &str,
+    tracks: usize,
+    sets: &[SymbolSet],
+) -> Result<Vec<TrackPattern>, String> {
+    let mut patterns = Vec::new();
+    for token in tokenize(text)? {
+        let pattern = match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(group) => parse_class(group, sets)?,
+            None if token == WILDCARD.to_string() => TrackPattern::Any,
+            None => TrackPattern::Symbol(token.chars().next().unwrap()),
+        };
+        patterns.push(pattern);
+    }
+    if patterns == [TrackPattern::Any] {
+        return Ok(vec![TrackPattern::Any; tracks]);
+    }
+    if patterns.len() != tracks {
+        return Err(format!(
+            "input '{}' has {} tracks, expected {}",
+            text,
+            patterns.len(),
+            tracks
+        ));
+    }
+    Ok(patterns)
+}
+
+/// Splits a rule's replacement into one write per track. A lone `?` keeps
+/// every track as it was read; a variable bound in the input, either bare or
+/// as `[d]`, writes the symbol it was bound to.
+pub fn parse_replacement(
+    text: &str,
+    tracks: usize,
+    bindings: &[String],
+) -> Result<Vec<TrackWrite>, String> {
+    let mut writes = Vec::new();
+    for token in tokenize(text)? {
+        let write = match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(name) if bindings.iter().any(|b| b == name.trim()) => {
+                TrackWrite::Variable(name.trim().to_string())
+            }
+            Some(name) => return Err(format!("unbound variable '{}'", name)),
+            None if bindings.contains(&token) => TrackWrite::Variable(token),
+            None if token == WILDCARD.to_string() => TrackWrite::Same,
+            None => TrackWrite::Symbol(token.chars().next().unwrap()),
+        };
+        writes.push(write);
+    }
+    if writes == [TrackWrite::Same] {
+        return Ok(vec![TrackWrite::Same; tracks]);
+    }
+    if writes.len() != tracks {
+        return Err(format!(
+            "replacement '{}' has {} tracks, expected {}",
+            text,
+            writes.len(),
+            tracks
+        ));
+    }
+    Ok(writes)
+}
+
+/// The variables bound by a rule's input.
+pub fn bindings(patterns: &[TrackPattern]) -> Vec<String> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match pattern {
+            TrackPattern::Class {
+                binding: Some(name),
+                ..
+            } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks that both sides of a rule have one symbol, wildcard or class per
+/// track, and that every set and variable it uses is defined.
+pub fn check(
+    function: &TransitionFunction,
+    tracks: usize,
+    sets: &[SymbolSet],
+) -> Result<(), String> {
+    let patterns = parse_pattern(&function.lhs.input, tracks, sets)?;
+    parse_replacement(&function.rhs.replacement, tracks, &bindings(&patterns))?;
+    Ok(())
+}
+
+/// (concrete symbols, classes): more of either makes a rule more specific.
+pub fn specificity(patterns: &[TrackPattern]) -> (usize, usize) {
+    let count = |f: fn(&TrackPattern) -> bool| patterns.iter().filter(|p| f(p)).count();
+    (
+        count(|p| matches!(p, TrackPattern::Symbol(_))),
+        count(|p| matches!(p, TrackPattern::Class { .. })),
+    )
+}
+
+/// The cell a rule leaves behind when it fires on `cell`, or `None` if it
+/// doesn't match the cell.
+pub fn apply(function: &TransitionFunction, machine: &Machine, cell: &str) -> Option<String> {
+    let patterns = parse_pattern(&function.lhs.input, machine.tracks, &machine.sets).ok()?;
+    let writes = parse_replacement(
+        &function.rhs.replacement,
+        machine.tracks,
+        &bindings(&patterns),
+    )
+    .ok()?;
+    let rule = CompiledRule {
+        index: 0,
+        patterns,
+        writes,
+    };
+    rule.matches(cell).then(|| rule.apply(cell))
+}
+
+/// A rule with its input and replacement parsed.
+struct CompiledRule {
+    index: usize,
+    patterns: Vec<TrackPattern>,
+    writes: Vec<TrackWrite>,
+}
+
+impl CompiledRule {
+    fn matches(&self, cell: &str) -> bool {
+        self.patterns.len() == cell.chars().count()
+            && self
+                .patterns
+                .iter()
+                .zip(cell.chars())
+                .all(|(track, c)| track.matches(c))
+    }
+
+    fn specificity(&self) -> (usize, usize) {
+        specificity(&self.patterns)
+    }
+
+    fn apply(&self, read: &str) -> String {
+        let mut bound: HashMap<&str, char> = HashMap::new();
+        for (pattern, c) in self.patterns.iter().zip(read.chars()) {
+            if let TrackPattern::Class {
+                binding: Some(name),
+                ..
+            } = pattern
+            {
+                bound.insert(name, c);
+            }
+        }
+        read.chars()
+            .zip(&self.writes)
+            .map(|(old, write)| match write {
+                TrackWrite::Symbol(c) => *c,
+                TrackWrite::Variable(name) => bound.get(name.as_str()).copied().unwrap_or(old),
+                TrackWrite::Same => old,
+            })
+            .collect()
+    }
+}
+
+struct StateRules {
+    exact: HashMap<String, usize>,
+    patterns: Vec<usize>,
+}
+
+/// Looks up the rule that applies to a state and the cell under the head.
+///
+/// Precedence, from strongest to weakest:
+/// 1. a rule whose input names the cell exactly;
+/// 2. the rule with the most tracks given as concrete symbols, then the most
+///    tracks given as symbol classes (`?` is the weakest match);
+/// 3. among equally specific rules, the one defined first.
+pub struct RuleIndex {
+    rules: Vec<CompiledRule>,
+    states: HashMap<String, StateRules>,
+}
+
+impl RuleIndex {
+    /// Rules that don't parse are left out; loaders reject them up front with
+    /// `check`.
+    pub fn new(machine: &Machine) -> RuleIndex {
+        let mut rules: Vec<CompiledRule> = Vec::new();
+        let mut states: HashMap<String, StateRules> = HashMap::new();
+        for (index, function) in machine.transitions.iter().enumerate() {
+            let Ok(patterns) = parse_pattern(&function.lhs.input, machine.tracks, &machine.sets)
+            else {
+                continue;
+            };
+            let Ok(writes) = parse_replacement(
+                &function.rhs.replacement,
+                machine.tracks,
+                &bindings(&patterns),
+            ) else {
+                continue;
+            };
+            let rule = CompiledRule {
+                index,
+                patterns,
+                writes,
+            };
+            let state_rules =
+                states
+                    .entry(function.lhs.state.clone())
+                    .or_insert_with(|| StateRules {
+                        exact: HashMap::new(),
+                        patterns: Vec::new(),
+                    });
+            if rule.specificity().0 == machine.tracks {
+                let cell: String = rule
+                    .patterns
+                    .iter()
+                    .filter_map(|track| match track {
+                        TrackPattern::Symbol(c) => Some(*c),
+                        _ => None,
+                    })
+                    .collect();
+                state_rules.exact.entry(cell).or_insert(rules.len());
+            } else {
+                state_rules.patterns.push(rules.len());
+            }
+            rules.push(rule);
+        }
+        for state_rules in states.values_mut() {
+            // stable sort keeps definition order among equally specific rules
+            state_rules
+                .patterns
+                .sort_by_key(|&rule| std::cmp::Reverse(rules[rule].specificity()));
+        }
+        RuleIndex { rules, states }
+    }
+
+    /// The rule that applies, as an index into `machine.transitions`, and
+    /// the cell it leaves behind.
+    pub fn fire(&self, state: &str, cell: &str) -> Option<(usize, String)> {
+        self.find_rule(state, cell)
+            .map(|rule| (rule.index, rule.apply(cell)))
+    }
+
+    fn find_rule(&self, state: &str, cell: &str) -> Option<&CompiledRule> {
+        let state_rules = self.states.get(state)?;
+        if let Some(&rule) = state_rules.exact.get(cell) {
+            return Some(&self.rules[rule]);
+        }
+        state_rules
+            .patterns
+            .iter()
+            .map(|&rule| &self.rules[rule])
+            .find(|rule| rule.matches(cell))
+    }
+}
+
+/// The symbols each track can hold: the declared tape alphabet Γ, or else
+/// what the rules use.
+pub fn tape_alphabet(machine: &Machine) -> Vec<Vec<char>> {
+    match &machine.tape_alphabet {
+        Some(gamma) => vec![gamma.clone(); machine.tracks],
+        None => inferred_alphabet(machine),
+    }
+}
+
+/// Every symbol a machine's rules mention on each track, plus the blank.
+pub fn inferred_alphabet(machine: &Machine) -> Vec<Vec<char>> {
+    let mut alphabet: Vec<Vec<char>> = vec![vec!['□']; machine.tracks];
+    let mut add = |track: usize, symbol: char| {
+        if !alphabet[track].contains(&symbol) {
+            alphabet[track].push(symbol);
+        }
+    };
+    for function in &machine.transitions {
+        let Ok(patterns) = parse_pattern(&function.lhs.input, machine.tracks, &machine.sets) else {
+            continue;
+        };
+        for (track, pattern) in patterns.iter().enumerate() {
+            match pattern {
+                TrackPattern::Symbol(c) => add(track, *c),
+                TrackPattern::Class { symbols, .. } => symbols.iter().for_each(|c| add(track, *c)),
+                TrackPattern::Any => {}
+            }
+        }
+        let Ok(writes) = parse_replacement(
+            &function.rhs.replacement,
+            machine.tracks,
+            &bindings(&patterns),
+        ) else {
+            continue;
+        };
+        for (track, write) in writes.iter().enumerate() {
+            match write {
+                TrackWrite::Symbol(c) => add(track, *c),
+                // whatever the class it was bound by can hold
+                TrackWrite::Variable(name) => {
+                    for pattern in &patterns {
+                        if let TrackPattern::Class {
+                            binding: Some(binding),
+                            symbols,
+                        } = pattern
+                        {
+                            if binding == name {
+                                symbols.iter().for_each(|c| add(track, *c));
+                            }
+                        }
+                    }
+                }
+                TrackWrite::Same => {}
+            }
+        }
+    }
+    alphabet
+}
+
+/// Every cell that can be made from one symbol per track.
+pub fn cells(alphabet: &[Vec<char>]) -> Vec<String> {
+    let mut cells = vec![String::new()];
+    for symbols in alphabet {
+        cells = cells
+            .iter()
+            .flat_map(|cell| symbols.iter().map(move |c| format!("{}{}", cell, c)))
+            .collect();
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::parse_machine;
+
+    fn machine(tracks: usize, rules: &str) -> Machine {
+        parse_machine(&format!(
+            "tracks: {}\ninitial: q0\ndigit = {{0..9}}\n{}",
+            tracks, rules
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn exact_cells_beat_literals_beat_classes_beat_wildcards() {
+        let machine = machine(
+            2,
+            "δ(q0,??)=(any,?,R)
+             δ(q0,[{a, b}]?)=(class,?,R)
+             δ(q0,a?)=(literal,?,R)
+             δ(q0,?b)=(later,?,R)
+             δ(q0,bb)=(exact,?,R)",
+        );
+        let index = RuleIndex::new(&machine);
+        let fired = |cell: &str| index.fire("q0", cell).map(|(rule, _)| rule);
+        assert_eq!(fired("bb"), Some(4));
+        // a? and ?b are equally specific, the one defined first wins
+        assert_eq!(fired("ab"), Some(2));
+        assert_eq!(fired("cb"), Some(3));
+        assert_eq!(fired("bc"), Some(1));
+        assert_eq!(fired("cc"), Some(0));
+        assert_eq!(index.fire("q1", "cc"), None);
+    }
+
+    #[test]
+    fn bound_symbols_are_written_back() {
+        let machine = machine(
+            2,
+            "δ(q0,[d∈digit]?)=(q0,?d,R)
+             δ(q0,[e:digit]x)=(q0,[e]e,R)",
+        );
+        let index = RuleIndex::new(&machine);
+        assert_eq!(index.fire("q0", "7a"), Some((0, String::from("77"))));
+        // the literal x makes the second rule more specific
+        assert_eq!(index.fire("q0", "3x"), Some((1, String::from("33"))));
+        assert_eq!(index.fire("q0", "a7"), None);
+        assert_eq!(
+            apply(&machine.transitions[1], &machine, "5x"),
+            Some(String::from("55"))
+        );
+        assert_eq!(apply(&machine.transitions[1], &machine, "5y"), None);
+    }
+
+    #[test]
+    fn patterns_must_cover_every_track() {
+        assert_eq!(
+            parse_pattern("a", 2, &[]).unwrap_err(),
+            "input 'a' has 1 tracks, expected 2"
+        );
+        assert_eq!(
+            parse_pattern("?", 3, &[]).unwrap(),
+            vec![TrackPattern::Any; 3]
+        );
+        assert_eq!(
+            parse_pattern("[digit]", 1, &[]).unwrap_err(),
+            "unknown symbol set 'digit'"
+        );
+        assert_eq!(
+            parse_replacement("[d]", 1, &[]).unwrap_err(),
+            "unbound variable 'd'"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::combine_tracks;
+use crate::definition;
+use crate::engine::{Run, Status};
+use crate::Machine;
+
+// A test case gives the input tracks, the expected outcome and optionally the
+// tape the machine should leave behind and the number of steps it takes:
+//
+//   abba -> accept
+//   1011 -> accept 1100 steps=9
+//   ab ba -> reject
+//   1 -> timeout
+//
+// Multi-track inputs and outputs are written one word per track, 'ε' stands
+// for an empty track and 'blank' can be used instead of □.
+//
+// Cases live in 'test:' lines of a machine definition or in a suite file
+// (.tests) of their own, one case per line, where 'limit: <n>' sets how many
+// steps a case may take before it counts as a timeout.
+
+/// Steps a case may take before it counts as a timeout.
+pub const STEP_LIMIT: usize = 10_000_000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Accept,
+    Reject,
+    /// Still running once the step limit is reached.
+    Timeout,
+}
+
+impl Outcome {
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Accept => "accept",
+            Outcome::Reject => "reject",
+            Outcome::Timeout => "timeout",
+        }
+    }
+}
+
+pub struct Case {
+    pub inputs: Vec<String>,
+    pub outcome: Outcome,
+    pub output: Option<Vec<String>>,
+    pub steps: Option<usize>,
+}
+
+pub struct Suite {
+    /// Each case along with how it is written.
+    pub cases: Vec<(String, Case)>,
+    pub limit: usize,
+}
+
+fn parse_tracks(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| match word {
+            "ε" => String::new(),
+            _ => word.replace("blank", "□"),
+        })
+        .collect()
+}
+
+pub fn parse_case(text: &str, tracks: usize) -> Result<Case, String> {
+    let Some((inputs, expected)) = text.split_once("->") else {
+        return Err(format!(
+            "'{}' should look like '<input> -> accept'",
+            text.trim()
+        ));
+    };
+    let inputs = parse_tracks(inputs);
+    let mut expected = parse_tracks(expected);
+    let outcome = match expected.first().map(|s| s.as_str()) {
+        Some("accept") => Outcome::Accept,
+        Some("reject") => Outcome::Reject,
+        Some("timeout") => Outcome::Timeout,
+        _ => {
+            return Err(format!(
+                "'{}' doesn't say accept, reject or timeout",
+                text.trim()
+            ))
+        }
+    };
+    let mut steps = None;
+    if let Some(count) = expected.last().and_then(|s| s.strip_prefix("steps=")) {
+        steps = Some(
+            count
+                .parse()
+                .map_err(|_| format!("'{}' is not a number of steps", count))?,
+        );
+        expected.pop();
+    }
+    let output = (expected.len() > 1).then(|| expected[1..].to_vec());
+    if inputs.len() != tracks || output.as_ref().is_some_and(|o| o.len() != tracks) {
+        return Err(format!(
+            "'{}' doesn't give {} track(s)",
+            text.trim(),
+            tracks
+        ));
+    }
+    if inputs
+        .iter()
+        .any(|s| s.chars().count() != inputs[0].chars().count())
+    {
+        return Err(format!("'{}' has tracks of different lengths", text.trim()));
+    }
+    Ok(Case {
+        inputs,
+        outcome,
+        output,
+        steps,
+    })
+}
+
+/// Parses a suite file; lines may also be written as 'test: <case>'.
+pub fn parse_suite(text: &str, tracks: usize) -> Result<Suite, String> {
+    let mut suite = Suite {
+        cases: Vec::new(),
+        limit: STEP_LIMIT,
+    };
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| format!("line {}: {}", number + 1, message);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(limit) = line.strip_prefix("limit:") {
+            suite.limit = limit
+                .trim()
+                .parse()
+                .map_err(|_| error(format!("'{}' is not a number of steps", limit.trim())))?;
+            continue;
+        }
+        let line = line.strip_prefix("test:").unwrap_or(line).trim();
+        let case = parse_case(line, tracks).map_err(error)?;
+        suite.cases.push((line.to_string(), case));
+    }
+    Ok(suite)
+}
+
+/// The cases in the 'test:' lines of a machine definition.
+pub fn definition_suite(text: &str, tracks: usize) -> Result<Suite, String> {
+    let lines: Vec<&str> = definition::properties(text, "test").collect();
+    parse_suite(&lines.join("\n"), tracks)
+}
+
+/// Lines showing where an expected track differs from the actual one.
+fn diff(track: &str, expected: &str, actual: &str) -> String {
+    let column = expected
+        .chars()
+        .zip(actual.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    format!(
+        "expected{}: {}\n     got{}: {}\n          {}{}^",
+        track,
+        if expected.is_empty() { "ε" } else { expected },
+        track,
+        if actual.is_empty() { "ε" } else { actual },
+        " ".repeat(track.chars().count()),
+        " ".repeat(column)
+    )
+}
+
+/// Runs a case for at most `limit` steps; the error says how the run
+/// differed from the expectation.
+pub fn run_case(machine: &Machine, case: &Case, limit: usize) -> Result<(), String> {
+    let mut run = Run::new(machine, &combine_tracks(&case.inputs));
+    let outcome = match run.run(limit) {
+        Status::Accepted => Outcome::Accept,
+        Status::Rejected | Status::Crashed => Outcome::Reject,
+        Status::Running => Outcome::Timeout,
+    };
+    let mut problems = Vec::new();
+    if outcome != case.outcome {
+        problems.push(match outcome {
+            Outcome::Timeout => format!(
+                "expected {}, still running after {} steps",
+                case.outcome.name(),
+                limit
+            ),
+            _ => format!("expected {}, got {}", case.outcome.name(), outcome.name()),
+        });
+    }
+    if let Some(expected) = &case.output {
+        let output = run.output();
+        for (track, (e, o)) in expected.iter().zip(&output).enumerate() {
+            if e != o {
+                let label = if machine.tracks > 1 {
+                    format!(" (track {})", track + 1)
+                } else {
+                    String::new()
+                };
+                problems.push(diff(&label, e, o));
+            }
+        }
+    }
+    if let Some(steps) = case.steps {
+        if run.steps != steps {
+            problems.push(format!("expected {} steps, took {}", steps, run.steps));
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_are_parsed() {
+        let case = parse_case("1011 -> accept 1100 steps=9", 1).unwrap();
+        assert_eq!(case.inputs, ["1011"]);
+        assert_eq!(case.outcome, Outcome::Accept);
+        assert_eq!(case.output, Some(vec![String::from("1100")]));
+        assert_eq!(case.steps, Some(9));
+
+        let case = parse_case("ab blankb -> timeout", 2).unwrap();
+        assert_eq!(case.inputs, ["ab", "□b"]);
+        assert_eq!(case.outcome, Outcome::Timeout);
+        assert_eq!(case.output, None);
+
+        let case = parse_case("ε ε -> reject ε ε", 2).unwrap();
+        assert_eq!(case.inputs, ["", ""]);
+        assert_eq!(case.output, Some(vec![String::new(), String::new()]));
+    }
+
+    #[test]
+    fn bad_cases_are_refused() {
+        let error = |text: &str, tracks: usize| parse_case(text, tracks).err().unwrap();
+        assert_eq!(
+            error("1011 accept", 1),
+            "'1011 accept' should look like '<input> -> accept'"
+        );
+        assert_eq!(
+            error("1 -> halt", 1),
+            "'1 -> halt' doesn't say accept, reject or timeout"
+        );
+        assert_eq!(
+            error("1 -> accept steps=few", 1),
+            "'few' is not a number of steps"
+        );
+        assert_eq!(
+            error("1 -> accept", 2),
+            "'1 -> accept' doesn't give 2 track(s)"
+        );
+        assert_eq!(
+            error("ab a -> accept", 2),
+            "'ab a -> accept' has tracks of different lengths"
+        );
+    }
+
+    #[test]
+    fn suites_are_parsed() {
+        let suite = parse_suite(include_str!("../example.tests"), 1).unwrap();
+        assert_eq!(suite.limit, 1000);
+        assert_eq!(suite.cases.len(), 4);
+        assert_eq!(suite.cases[3].0, "ε -> reject");
+        assert_eq!(
+            parse_suite("1 -> accept\nlimit: soon", 1).err().unwrap(),
+            "line 2: 'soon' is not a number of steps"
+        );
+        assert_eq!(
+            parse_suite("test: 1 -> accept\n\ntest: 1", 1)
+                .err()
+                .unwrap(),
+            "line 3: '1' should look like '<input> -> accept'"
+        );
+    }
+
+    #[test]
+    fn failing_cases_say_what_differs() {
+        let machine = definition::parse_machine(include_str!("../example.tm")).unwrap();
+        let suite = parse_suite(include_str!("../example.tests"), 1).unwrap();
+        for (_, case) in &suite.cases {
+            assert_eq!(run_case(&machine, case, suite.limit), Ok(()));
+        }
+        let case = parse_case("11011 -> reject 11111 steps=3", 1).unwrap();
+        assert_eq!(
+            run_case(&machine, &case, suite.limit).unwrap_err(),
+            [
+                "expected reject, got accept",
+                "expected: 11111",
+                "     got: 11110",
+                "              ^",
+                "expected 3 steps, took 12",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+/// How the tape behaves at its left end.
+///
+/// `TwoWay` is the classic infinite-in-both-directions tape. The one-way
+/// variants start the tape at the first input cell; moving left from there
+/// either leaves the head where it is or crashes the machine.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TapeMode {
+    TwoWay,
+    OneWayStay,
+    OneWayCrash,
+}
+
+impl TapeMode {
+    pub const ALL: [TapeMode; 3] = [
+        TapeMode::TwoWay,
+        TapeMode::OneWayStay,
+        TapeMode::OneWayCrash,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TapeMode::TwoWay => "two-way",
+            TapeMode::OneWayStay => "one-way-stay",
+            TapeMode::OneWayCrash => "one-way-crash",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TapeMode> {
+        TapeMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name.trim().to_lowercase())
+    }
+
+    pub fn is_one_way(&self) -> bool {
+        *self != TapeMode::TwoWay
+    }
+
+    /// Where the head ends up after moving left from `head`, or `None` if the
+    /// machine crashes off the left end.
+    pub fn move_left(&self, head: i64) -> Option<i64> {
+        match self {
+            TapeMode::TwoWay => Some(head - 1),
+            TapeMode::OneWayStay => Some((head - 1).max(0)),
+            TapeMode::OneWayCrash if head == 0 => None,
+            TapeMode::OneWayCrash => Some(head - 1),
+        }
+    }
+}
+
+/// Compact id of a tape cell. A cell holds one character per track.
+pub type Symbol = u32;
+
+/// Interns cell strings so that the tape can store plain ids. The blank cell
+/// is always `Symbols::BLANK`.
+pub struct Symbols {
+    names: Vec<String>,
+    ids: HashMap<String, Symbol>,
+}
+
+impl Symbols {
+    pub const BLANK: Symbol = 0;
+
+    pub fn new(tracks: usize) -> Symbols {
+        let mut symbols = Symbols {
+            names: Vec::new(),
+            ids: HashMap::new(),
+        };
+        symbols.intern(&"□".repeat(tracks));
+        symbols
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as Symbol;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn name(&self, symbol: Symbol) -> &str {
+        &self.names[symbol as usize]
+    }
+}
+
+/// A tape that grows in O(1) at either end.
+///
+/// Cells are addressed by logical position: the first input cell is 0 and
+/// positions to its left are negative, so a position keeps naming the same
+/// cell however much the tape grows.
+pub struct Tape {
+    cells: VecDeque<Symbol>,
+    origin: i64,
+}
+
+impl Tape {
+    pub fn new(input: &[Symbol], mode: TapeMode) -> Tape {
+        let mut cells: VecDeque<Symbol> = input.iter().copied().collect();
+        cells.push_back(Symbols::BLANK);
+        let mut origin = 0;
+        if !mode.is_one_way() {
+            cells.push_front(Symbols::BLANK);
+            origin = -1;
+        }
+        Tape { cells, origin }
+    }
+
+    pub fn read(&self, position: i64) -> Symbol {
+        let index = position - self.origin;
+        if index < 0 {
+            return Symbols::BLANK;
+        }
+        self.cells
+            .get(index as usize)
+            .copied()
+            .unwrap_or(Symbols::BLANK)
+    }
+
+    pub fn write(&mut self, position: i64, symbol: Symbol) {
+        self.reach(position);
+        let index = (position - self.origin) as usize;
+        self.cells[index] = symbol;
+    }
+
+    /// Extends the tape with blanks so that `position` is a stored cell.
+    pub fn reach(&mut self, position: i64) {
+        while position < self.origin {
+            self.cells.push_front(Symbols::BLANK);
+            self.origin -= 1;
+        }
+        while position >= self.origin + self.cells.len() as i64 {
+            self.cells.push_back(Symbols::BLANK);
+        }
+    }
+
+    /// Logical positions of the stored cells.
+    pub fn span(&self) -> Range<i64> {
+        self.origin..self.origin + self.cells.len() as i64
+    }
+}
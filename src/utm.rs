@@ -0,0 +1,412 @@
+use crate::demos;
+use crate::engine::Run;
+use crate::normalize;
+use crate::oracle::{self, Verdict};
+use crate::rules::{self, RuleIndex};
+use crate::suite;
+use crate::tape::TapeMode;
+use crate::{KeyStates, Machine, TransitionFunction, LHS, RHS};
+
+// The standard encoding of a machine and its input, the one the utm demo
+// runs on:
+//
+//   <register> #<rule> #<rule> ... @ ^<cell> |<cell> ...
+//
+// States are numbered in binary behind a bit that is 1 for final states, the
+// initial state first, then in the order the rules mention them. Symbols are
+// numbered with the blank as 0 and the rest in the order of Γ. The register
+// starts as the initial state and an all-zero symbol, and a rule δ(p,a) =
+// (q,b,D) is written 'p:a>q:bD'. Rules with wildcards or sets are written out
+// once for every symbol they fire on.
+
+/// A machine and input written in the standard encoding, with the numbering
+/// used for its states and symbols.
+pub struct Encoding {
+    pub text: String,
+    pub states: Vec<String>,
+    pub symbols: Vec<char>,
+}
+
+impl Encoding {
+    /// The simulated tape on a tape the utm demo leaves behind, trimmed like
+    /// `Run::output`.
+    pub fn decode_tape(&self, tape: &str) -> Result<String, String> {
+        let mut cells = Vec::new();
+        for bits in tape_cells(tape)? {
+            let symbol = self
+                .symbols
+                .get(number(&bits)?)
+                .ok_or(format!("no symbol is numbered {}", bits))?;
+            cells.push(*symbol);
+        }
+        let output: String = cells.into_iter().collect();
+        Ok(output.trim_matches('□').to_string())
+    }
+
+    /// The number of each state and symbol, e.g. 'q0 = 00, q1 = 01'.
+    pub fn legend(&self) -> (String, String) {
+        let (states, symbols) = (width(self.states.len()), width(self.symbols.len()));
+        let numbered = |names: Vec<String>, width: usize| -> String {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{} = {}", name, bits(i, width)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        (
+            numbered(self.states.clone(), states),
+            numbered(
+                self.symbols.iter().map(|c| c.to_string()).collect(),
+                symbols,
+            ),
+        )
+    }
+}
+
+/// The fewest bits that number `count` things, at least one.
+fn width(count: usize) -> usize {
+    let mut width = 1;
+    while (1 << width) < count {
+        width += 1;
+    }
+    width
+}
+
+fn bits(n: usize, width: usize) -> String {
+    format!("{:0width$b}", n, width = width)
+}
+
+fn number(bits: &str) -> Result<usize, String> {
+    usize::from_str_radix(bits, 2).map_err(|_| format!("'{}' isn't a binary number", bits))
+}
+
+/// The bits of each cell after '@', with marked bits read as plain ones.
+fn tape_cells(tape: &str) -> Result<Vec<String>, String> {
+    let (_, cells) = tape.split_once('@').ok_or("the tape has no '@'")?;
+    Ok(cells
+        .split(['^', '|'])
+        .filter(|cell| !cell.is_empty())
+        .map(|cell| {
+            cell.chars()
+                .map(|c| match c {
+                    'o' => '0',
+                    'i' => '1',
+                    c => c,
+                })
+                .filter(|&c| c != '□')
+                .collect()
+        })
+        .filter(|cell: &String| !cell.is_empty())
+        .collect())
+}
+
+pub fn encode(machine: &Machine, input: &str) -> Result<Encoding, String> {
+    if machine.tracks != 1 {
+        return Err(String::from("only single-track machines can be encoded"));
+    }
+    if machine.tape_mode != TapeMode::TwoWay {
+        return Err(format!(
+            "the machine runs on a {} tape, the encoding is for the two-way tape",
+            machine.tape_mode.name()
+        ));
+    }
+    let mut symbols = vec!['□'];
+    for c in rules::tape_alphabet(machine).remove(0) {
+        if !symbols.contains(&c) {
+            symbols.push(c);
+        }
+    }
+    if let Some(c) = input.chars().find(|c| !symbols.contains(c)) {
+        return Err(format!("'{}' isn't in the tape alphabet", c));
+    }
+    let states = normalize::all_states(machine);
+
+    let state_width = width(states.len());
+    let symbol_width = width(symbols.len());
+    let state = |name: &str| {
+        let flag = if machine.states.final_states.iter().any(|f| f == name) {
+            '1'
+        } else {
+            '0'
+        };
+        let index = states.iter().position(|s| s == name).unwrap_or(0);
+        format!("{}{}", flag, bits(index, state_width))
+    };
+    let symbol = |c: char| {
+        bits(
+            symbols.iter().position(|&s| s == c).unwrap_or(0),
+            symbol_width,
+        )
+    };
+
+    let mut text = format!(
+        "{}:{}",
+        state(&machine.states.initial_state),
+        "0".repeat(symbol_width)
+    );
+    // in the order of the rules, each once for every symbol it fires on
+    let index = RuleIndex::new(machine);
+    for (rule, function) in machine.transitions.iter().enumerate() {
+        let lhs = &function.lhs.state;
+        for &c in &symbols {
+            match index.fire(lhs, &c.to_string()) {
+                Some((fired, written)) if fired == rule => {
+                    let written = written.chars().next().unwrap_or('□');
+                    text += &format!(
+                        "#{}:{}>{}:{}{}",
+                        state(lhs),
+                        symbol(c),
+                        state(&function.rhs.state),
+                        symbol(written),
+                        function.rhs.direction
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    text.push('@');
+    let cells: Vec<char> = if input.is_empty() {
+        vec!['□']
+    } else {
+        input.chars().collect()
+    };
+    for (i, &c) in cells.iter().enumerate() {
+        text.push(if i == 0 { '^' } else { '|' });
+        text += &symbol(c);
+    }
+    Ok(Encoding {
+        text,
+        states,
+        symbols,
+    })
+}
+
+/// Reads a machine and its input back from the standard encoding. States are
+/// named q0, q1, ... as their names aren't encoded. Symbols 1, 2, ... are the
+/// given `symbols` in order, as in `Encoding::symbols` after the blank, or
+/// else named by their number; 0 is always the blank.
+pub fn decode(text: &str, symbols: Option<&[char]>) -> Result<(Machine, String), String> {
+    let (program, _) = text.split_once('@').ok_or("the encoding has no '@'")?;
+    let mut parts = program.split('#');
+    let register = parts.next().unwrap_or_default();
+    let (initial, blank) = register
+        .split_once(':')
+        .ok_or("the register isn't written '<state>:<symbol>'")?;
+    let (state_width, symbol_width) = (initial.len(), blank.len());
+    if state_width < 2 || symbol_width == 0 {
+        return Err(String::from("the register is too short"));
+    }
+
+    let mut final_states: Vec<String> = Vec::new();
+    let mut state = |bits: &str| -> Result<String, String> {
+        if bits.len() != state_width {
+            return Err(format!("'{}' isn't a state of {} bits", bits, state_width));
+        }
+        let name = format!("q{}", number(&bits[1..])?);
+        if bits.starts_with('1') && !final_states.contains(&name) {
+            final_states.push(name.clone());
+        }
+        Ok(name)
+    };
+    let symbol = |bits: &str| -> Result<char, String> {
+        if bits.len() != symbol_width {
+            return Err(format!(
+                "'{}' isn't a symbol of {} bits",
+                bits, symbol_width
+            ));
+        }
+        match (number(bits)?, symbols) {
+            (0, _) => Ok('□'),
+            (n, Some(symbols)) => symbols
+                .get(n - 1)
+                .copied()
+                .ok_or(format!("no symbol is given for number {}", n)),
+            (n, None) if n < 36 => Ok(char::from_digit(n as u32, 36).unwrap()),
+            (n, None) => Err(format!("symbol {} can't be named, at most 35 can", n)),
+        }
+    };
+
+    let initial_state = state(initial)?;
+    let mut transitions = Vec::new();
+    for rule in parts {
+        let invalid = || format!("'{}' isn't a rule 'p:a>q:bD'", rule);
+        let (lhs, rhs) = rule.split_once('>').ok_or_else(invalid)?;
+        let (p, a) = lhs.split_once(':').ok_or_else(invalid)?;
+        let (q, write) = rhs.split_once(':').ok_or_else(invalid)?;
+        let direction = write.chars().last().ok_or_else(invalid)?;
+        if direction != 'L' && direction != 'R' {
+            return Err(invalid());
+        }
+        let b = &write[..write.len() - 1];
+        transitions.push(TransitionFunction {
+            lhs: LHS {
+                state: state(p)?,
+                input: symbol(a)?.to_string(),
+            },
+            rhs: RHS {
+                state: state(q)?,
+                replacement: symbol(b)?.to_string(),
+                direction,
+            },
+        });
+    }
+    if !text.contains("@^") {
+        return Err(String::from("the head must be on the first cell"));
+    }
+    let mut input = String::new();
+    for bits in tape_cells(text)? {
+        input.push(symbol(&bits)?);
+    }
+
+    let machine = Machine {
+        transitions,
+        states: KeyStates {
+            initial_state,
+            final_states,
+        },
+        tracks: 1,
+        tape_mode: TapeMode::TwoWay,
+        sets: Vec::new(),
+        input_alphabet: None,
+        tape_alphabet: symbols.map(|symbols| {
+            let mut gamma = vec!['□'];
+            gamma.extend(symbols);
+            gamma
+        }),
+    };
+    Ok((machine, input.trim_matches('□').to_string()))
+}
+
+/// Steps taken on the inputs of one length.
+pub struct Simulation {
+    pub length: usize,
+    pub inputs: usize,
+    /// The most steps the machine took itself.
+    pub steps: usize,
+    /// The most steps the utm demo took to simulate it.
+    pub simulated: usize,
+}
+
+/// Runs the machine and the utm demo on its encoding for every input up to
+/// `max_length` symbols, checking that they agree on the verdict and the
+/// tape left behind. Inputs the machine doesn't halt on are left out.
+pub fn cross_check(machine: &Machine, max_length: usize) -> Result<Vec<Simulation>, String> {
+    let utm = demos::find("utm")
+        .ok_or("the utm demo is missing")?
+        .machine();
+    let alphabet = oracle::input_alphabet(machine);
+    let mut report: Vec<Simulation> = (0..=max_length)
+        .map(|length| Simulation {
+            length,
+            inputs: 0,
+            steps: 0,
+            simulated: 0,
+        })
+        .collect();
+    for input in oracle::inputs(&alphabet, max_length) {
+        let mut native = Run::new(machine, &input);
+        let verdict = Verdict::of(native.run(oracle::STEP_LIMIT));
+        if verdict == Verdict::Timeout {
+            continue;
+        }
+        let encoding = encode(machine, &input)?;
+        let mut simulation = Run::new(&utm, &encoding.text);
+        let simulated = Verdict::of(simulation.run(suite::STEP_LIMIT));
+        let shown = if input.is_empty() { "ε" } else { &input };
+        if simulated != verdict {
+            return Err(format!(
+                "the utm differs on input '{}': {} natively, {} simulated",
+                shown,
+                verdict.name(),
+                simulated.name()
+            ));
+        }
+        let expected = native.output().remove(0);
+        let output = encoding.decode_tape(&simulation.output().remove(0))?;
+        if output != expected {
+            return Err(format!(
+                "the utm leaves a different tape on input '{}': {} natively, {} simulated",
+                shown, expected, output
+            ));
+        }
+        let row = &mut report[input.chars().count()];
+        row.inputs += 1;
+        row.steps = row.steps.max(native.steps);
+        row.simulated = row.simulated.max(simulation.steps);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_an_encoding_gives_back_the_machine_and_input() {
+        for name in ["increment", "decrement", "palindrome", "anbn", "copy"] {
+            let machine = demos::find(name).unwrap().machine();
+            for input in oracle::inputs(&oracle::input_alphabet(&machine), 4) {
+                let encoding = encode(&machine, &input).unwrap();
+                let (decoded, decoded_input) =
+                    decode(&encoding.text, Some(&encoding.symbols[1..])).unwrap();
+                assert_eq!(decoded_input, input, "{} on '{}'", name, input);
+                assert_eq!(decoded.tape_alphabet.as_ref(), Some(&encoding.symbols));
+                assert_eq!(encode(&decoded, &input).unwrap().text, encoding.text);
+                let mut original = Run::new(&machine, &input);
+                let mut copy = Run::new(&decoded, &input);
+                assert_eq!(
+                    original.run(oracle::STEP_LIMIT),
+                    copy.run(oracle::STEP_LIMIT),
+                    "{} on '{}'",
+                    name,
+                    input
+                );
+                assert_eq!(original.output(), copy.output(), "{} on '{}'", name, input);
+            }
+        }
+    }
+
+    #[test]
+    fn symbols_are_named_by_number_unless_given() {
+        let machine = demos::find("increment").unwrap().machine();
+        let encoding = encode(&machine, "10").unwrap();
+        assert_eq!(encoding.symbols, ['□', '0', '1']);
+        let (decoded, input) = decode(&encoding.text, None).unwrap();
+        assert_eq!(input, "21");
+        assert_eq!(decoded.tape_alphabet, None);
+        assert_eq!(
+            decode(&encoding.text, Some(&['0'])).err().unwrap(),
+            "no symbol is given for number 2"
+        );
+    }
+
+    #[test]
+    fn machines_the_utm_cant_run_are_refused() {
+        let tracks = demos::find("tracks").unwrap().machine();
+        assert_eq!(
+            encode(&tracks, "").err().unwrap(),
+            "only single-track machines can be encoded"
+        );
+        let mut one_way = demos::find("increment").unwrap().machine();
+        one_way.tape_mode = TapeMode::OneWayCrash;
+        assert_eq!(
+            encode(&one_way, "").err().unwrap(),
+            "the machine runs on a one-way-crash tape, the encoding is for the two-way tape"
+        );
+        let machine = demos::find("increment").unwrap().machine();
+        assert_eq!(
+            encode(&machine, "12").err().unwrap(),
+            "'2' isn't in the tape alphabet"
+        );
+        assert_eq!(
+            decode("000:00", None).err().unwrap(),
+            "the encoding has no '@'"
+        );
+        assert_eq!(
+            decode("000:00#000:00>001:0@^00", None).err().unwrap(),
+            "'000:00>001:0' isn't a rule 'p:a>q:bD'"
+        );
+    }
+}